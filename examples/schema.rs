@@ -3,8 +3,9 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use fund_forwarding::msg::{HandleMsg, InitMsg, QueryMsg};
+use fund_forwarding::msg::{HandleAnswer, HandleMsg, InitMsg, QueryAnswer, QueryMsg};
 use fund_forwarding::state::Config;
+use fund_forwarding::factory;
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -15,5 +16,17 @@ fn main() {
     export_schema(&schema_for!(InitMsg), &out_dir);
     export_schema(&schema_for!(HandleMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(HandleAnswer), &out_dir);
+    export_schema(&schema_for!(QueryAnswer), &out_dir);
     export_schema(&schema_for!(Config), &out_dir);
+
+    out_dir.push("factory");
+    create_dir_all(&out_dir).unwrap();
+    remove_schemas(&out_dir).unwrap();
+
+    export_schema(&schema_for!(factory::msg::InitMsg), &out_dir);
+    export_schema(&schema_for!(factory::msg::HandleMsg), &out_dir);
+    export_schema(&schema_for!(factory::msg::QueryMsg), &out_dir);
+    export_schema(&schema_for!(factory::msg::HandleAnswer), &out_dir);
+    export_schema(&schema_for!(factory::msg::QueryAnswer), &out_dir);
 }
@@ -1,18 +1,39 @@
 use cosmwasm_std::{
-    to_binary, Api, Binary, CosmosMsg, Env, Extern, HandleResponse, HandleResult, HumanAddr, InitResponse, Uint128, Querier,
-    StdError, StdResult, Storage, CanonicalAddr, QueryResult,
+    log, from_binary, to_binary, Api, BankMsg, Binary, BlockInfo, Coin, CosmosMsg, Env, Extern, HandleResponse, HandleResult, HumanAddr, InitResponse, LogAttribute, Uint128, Querier, WasmMsg,
+    StdError, StdResult, Storage, CanonicalAddr, QueryResult, ReadonlyStorage,
 };
 use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
 
-use crate::msg::{HandleMsg, InitMsg, QueryAnswer, QueryMsg};
-use crate::state::{save, load, may_load, remove, Config, CONFIG_KEY, PREFIX_TOKEN_CONTRACT_INFO, FUNDS_DISTRIBUTION_KEY};
-use crate::royalties::{RoyaltyInfo, StoredRoyaltyInfo};
+use crate::msg::{AdminAction, DistributionProposalInfo, EpochStats, ForwardPayout, ForwardRecord, ForwardSummary, HeightRange, HandleAnswer, HandleMsg, InitMsg, Payout, PayoutRecord, PendingDistribution, ProposalInfo, ReceiveMsg, RegisteredToken, QueryAnswer, QueryMsg, QueryWithPermit, ResponseStatus, SplitInfo, TokenBalance, TokenError, TokenTotal};
+use crate::state::{save, load, may_load, remove, Config, DistKey, DistributionMode, PendingTimelock, RegistrationPolicy, StoredDistProposal, StoredForward, StoredPayoutRecord, StoredPendingDistribution, StoredProposal, StoredSplit, StoredStats, StoredTokenInfo, Timelock, CONFIG_KEY, PREFIX_TOKEN_CONTRACT_INFO, FUNDS_DISTRIBUTION_KEY, PREFIX_TOKEN_DISTRIBUTION, PREFIX_CLAIMABLE,
+    PRNG_SEED_KEY, PREFIX_VIEW_KEY, PREFIX_REVOKED_PERMITS, PREFIX_EARNINGS, PREFIX_EARNED_TOKENS, PREFIX_PAYOUT_HISTORY, PREFIX_PROPOSALS, PROPOSAL_COUNT_KEY,
+    PENDING_DISTRIBUTION_KEY, PREFIX_PENDING_TOKEN_DISTRIBUTION, PREFIX_DIST_PROPOSALS, DIST_PROPOSAL_COUNT_KEY,
+    REGISTERED_TOKENS_KEY, PREFIX_PENDING_RECIPIENT,
+    PREFIX_TOTAL_CLAIMABLE, PREFIX_TOKEN_VIEWING_KEY, PREFIX_TOKEN_PAYOUT_HISTORY, PREFIX_FORWARD_LEDGER, PREFIX_TOKEN_FORWARDS,
+    PREFIX_TOKEN_STATS, PREFIX_EPOCH_STATS, PREFIX_EPOCH_PAID, PREFIX_TOKEN_RECIPIENTS, DEFAULT_EPOCH_LENGTH, DEFAULT_MAX_FORWARD_DEPTH,
+    PREFIX_NAMED_DISTRIBUTION, PREFIX_PENDING_NAMED_DISTRIBUTION, DISTRIBUTION_NAMES_KEY,
+    PREFIX_SPLITS, SPLIT_COUNT_KEY, PREFIX_PENDING_SPLIT_DISTRIBUTION, PREFIX_SPLIT_LEDGER};
+use crate::royalties::{RoyaltyInfo, Split, StoredPayout, StoredRoyaltyInfo};
+use crate::viewing_key::{sha_256, ViewingKey, VIEWING_KEY_SIZE};
 
 
 use primitive_types::U256;
-use secret_toolkit::{snip20::handle::{register_receive_msg,transfer_msg}};
+use secret_toolkit::{snip20::handle::{register_receive_msg,send_msg,transfer_msg,set_viewing_key_msg}};
+use secret_toolkit::snip20::query::{balance_query, token_info_query, TokenInfo};
+use secret_toolkit::permit::{validate, Permit, RevokedPermits, TokenPermissions};
+use secret_toolkit::storage::{AppendStore, AppendStoreMut};
+use secret_toolkit::utils::pad_handle_result;
 
 pub const BLOCK_SIZE: usize = 256;
+/// maximum number of epochs a stats query can span
+pub const MAX_QUERY_EPOCHS: u64 = 100;
+/// maximum length in bytes of a distribution name
+pub const MAX_DISTRIBUTION_NAME_LEN: usize = 64;
+/// maximum length in bytes of a split name
+pub const MAX_SPLIT_NAME_LEN: usize = 64;
+/// number of tokens ever registered beyond which only admins can register more.  Moving a
+/// recipient and the balance queries go through every registered token
+pub const MAX_OPEN_REGISTRATIONS: usize = 50;
 
 
 pub fn init<S: Storage, A: Api, Q: Querier>(
@@ -20,13 +41,35 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
     env: Env,
     msg: InitMsg,
 ) -> StdResult<InitResponse> {
+    let mut admins = vec![msg.admin];
+    admins.extend(msg.co_admins);
+    let epoch_length = msg.stats_epoch_length.unwrap_or(DEFAULT_EPOCH_LENGTH);
+    if epoch_length == 0 {
+        return Err(StdError::generic_err("Stats epochs must be at least one block long"));
+    }
+    let max_forward_depth = msg.max_forward_depth.unwrap_or(DEFAULT_MAX_FORWARD_DEPTH);
+    if max_forward_depth == 0 {
+        return Err(StdError::generic_err("The max forward depth must be at least 1"));
+    }
     let config = Config {
-        admin: deps.api.canonical_address(&msg.admin)?,
+        admins: validate_admins(&deps.api, &admins, msg.admin_threshold.unwrap_or(1))?,
+        threshold: msg.admin_threshold.unwrap_or(1),
+        distribution_mode: msg.distribution_mode,
+        contract_address: deps.api.canonical_address(&env.contract.address)?,
+        hide_recipients: msg.hide_recipients,
+        pending_admin: None,
+        timelock: msg.timelock,
+        pending_timelock: None,
+        locked_recipients: msg.locked_recipients,
+        registration_policy: msg.registration_policy,
+        epoch_length,
+        max_forward_depth,
     };
 
     store_dist_info(
         &mut deps.storage,
         &deps.api,
+        &config.contract_address,
         Some(msg.dist_info).as_ref(),
         None,
         FUNDS_DISTRIBUTION_KEY
@@ -35,23 +78,47 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
 
    
     save(&mut deps.storage, CONFIG_KEY, &config)?;
+    let prng_seed: Vec<u8> = sha_256(&msg.prng_seed.0).to_vec();
+    save(&mut deps.storage, PRNG_SEED_KEY, &prng_seed)?;
 
 
     // Store sscrt in registered contracts
+    let sscrt_token_info = fetch_token_info(&deps.querier, &msg.sscrt_addr, &msg.sscrt_hash)?;
+    let sscrt_info = StoredTokenInfo {
+        code_hash: msg.sscrt_hash.clone(),
+        name: sscrt_token_info.name,
+        symbol: sscrt_token_info.symbol,
+        decimals: sscrt_token_info.decimals,
+        registered_height: env.block.height,
+        registered: true,
+    };
     let mut snip_contract_storage = PrefixedStorage::new(PREFIX_TOKEN_CONTRACT_INFO, &mut deps.storage);
-    save(&mut snip_contract_storage, msg.sscrt_addr.0.as_bytes(), &msg.sscrt_hash)?;
+    save(&mut snip_contract_storage, msg.sscrt_addr.0.as_bytes(), &sscrt_info)?;
+    save(&mut deps.storage, REGISTERED_TOKENS_KEY, &vec![msg.sscrt_addr.clone()])?;
+    let sscrt_key_msg = token_key_msg(&mut deps.storage, &env, &msg.sscrt_addr, &msg.sscrt_hash)?;
+
+    let mut messages = vec![
+        register_receive_msg(
+            env.contract_code_hash,
+            None,
+            BLOCK_SIZE,
+            msg.sscrt_hash,
+            msg.sscrt_addr
+        )?,
+        sscrt_key_msg,
+    ];
+    if let Some(callback) = msg.callback {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: callback.contract_addr,
+            callback_code_hash: callback.code_hash,
+            msg: callback.msg,
+            send: vec![],
+        }));
+    }
 
 
     Ok(InitResponse {
-        messages: vec![
-            register_receive_msg(
-                env.contract_code_hash,
-                None,
-                BLOCK_SIZE,
-                msg.sscrt_hash,
-                msg.sscrt_addr
-            )?
-        ],
+        messages,
         log: vec![],
     })
 }
@@ -61,213 +128,2118 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
     env: Env,
     msg: HandleMsg,
 ) -> StdResult<HandleResponse> {
-    match msg {
+    let response = match msg {
         HandleMsg::Receive { sender, from, amount, msg } => receive(deps, env, sender, from, amount, msg),
-        HandleMsg::RegisterToken { snip20_addr, snip20_hash } => register_token(deps, env, snip20_addr, snip20_hash),
-        HandleMsg::ChangeDistribution { dist_info } => change_dist(deps, env, dist_info),
-        HandleMsg::ChangeAdmin { admin_addr } => change_admin(deps, env, admin_addr),
-    }
+        HandleMsg::RegisterToken { snip20_addr, snip20_hash, dist_info } => try_register_token(deps, env, snip20_addr, snip20_hash, dist_info),
+        HandleMsg::SetTokenDistribution { snip20_addr, dist_info } => admin_action(deps, env, AdminAction::SetTokenDistribution { snip20_addr, dist_info }),
+        HandleMsg::UnregisterToken { snip20_addr } => admin_action(deps, env, AdminAction::UnregisterToken { snip20_addr }),
+        HandleMsg::Sweep { token, amount, recipient } => admin_action(deps, env, AdminAction::Sweep { token, amount, recipient }),
+        HandleMsg::SweepNative { amount, recipient } => admin_action(deps, env, AdminAction::SweepNative { amount, recipient }),
+        HandleMsg::RedistributeBalance { token } => admin_action(deps, env, AdminAction::RedistributeBalance { token }),
+        HandleMsg::SetTokenViewingKey { token, key } => admin_action(deps, env, AdminAction::SetTokenViewingKey { token, key }),
+        HandleMsg::ForwardNative { distribution, split_id } => forward_native(deps, env, distribution, split_id),
+        HandleMsg::CreateSplit { name, dist_info, admin } => create_split(deps, env, name, dist_info, admin),
+        HandleMsg::ChangeSplitDistribution { split_id, dist_info } => change_split_dist(deps, env, split_id, dist_info),
+        HandleMsg::ProposeSplitAdmin { split_id, admin_addr } => propose_split_admin(deps, env, split_id, admin_addr),
+        HandleMsg::AcceptSplitAdmin { split_id } => accept_split_admin(deps, env, split_id),
+        HandleMsg::ChangeDistribution { dist_info } => admin_action(deps, env, AdminAction::ChangeDistribution { dist_info }),
+        HandleMsg::SetNamedDistribution { name, dist_info } => admin_action(deps, env, AdminAction::SetNamedDistribution { name, dist_info }),
+        HandleMsg::ProposeAdmin { admin_addr } => admin_action(deps, env, AdminAction::ProposeAdmin { admin_addr }),
+        HandleMsg::AcceptAdmin {} => accept_admin(deps, env),
+        HandleMsg::CancelAdminProposal {} => admin_action(deps, env, AdminAction::CancelAdminProposal {}),
+        HandleMsg::ChangeAdmins { admins, threshold } => admin_action(deps, env, AdminAction::ChangeAdmins { admins, threshold }),
+        HandleMsg::ApproveProposal { proposal_id } => approve_proposal(deps, env, proposal_id),
+        HandleMsg::SetDistributionMode { mode } => admin_action(deps, env, AdminAction::SetDistributionMode { mode }),
+        HandleMsg::SetHideRecipients { hide_recipients } => admin_action(deps, env, AdminAction::SetHideRecipients { hide_recipients }),
+        HandleMsg::SetTimelock { timelock } => admin_action(deps, env, AdminAction::SetTimelock { timelock }),
+        HandleMsg::CancelDistributionChange { token, distribution } => {
+            admin_action(deps, env, AdminAction::CancelDistributionChange { token, distribution })
+        }
+        HandleMsg::LockRecipients {} => admin_action(deps, env, AdminAction::LockRecipients {}),
+        HandleMsg::SetRegistrationPolicy { policy } => admin_action(deps, env, AdminAction::SetRegistrationPolicy { policy }),
+        HandleMsg::SetMaxForwardDepth { max_forward_depth } => {
+            admin_action(deps, env, AdminAction::SetMaxForwardDepth { max_forward_depth })
+        }
+        HandleMsg::ApproveDistributionChange { proposal_id } => approve_dist_change(deps, env, proposal_id),
+        HandleMsg::ChangeMyRecipientAddress { new_addr, require_acceptance } => change_recipient_addr(deps, env, new_addr, require_acceptance),
+        HandleMsg::AcceptRecipientAddress { old_addr } => accept_recipient_addr(deps, env, old_addr),
+        HandleMsg::Claim { tokens } => claim(deps, env, tokens),
+        HandleMsg::CreateViewingKey { entropy, .. } => create_key(deps, env, entropy),
+        HandleMsg::SetViewingKey { key, .. } => set_key(deps, env, key),
+        HandleMsg::RevokePermit { permit_name, .. } => revoke_permit(deps, env, permit_name),
+    };
+    pad_handle_result(response, BLOCK_SIZE)
 }
 
 
 
 
 
-/// For receiving SNIP20s
+/// For receiving SNIP20s, along with any native coins sent with them.  Funds that have already
+/// passed through the max forward depth of forwarders are rejected, so loops of forwarders
+/// paying each other end instead of bouncing funds until gas runs out.  A send msg that is not
+/// a ReceiveMsg is rejected too
 pub fn receive<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     _sender: HumanAddr,
-    _from: HumanAddr,
+    from: HumanAddr,
     amount: Uint128,
-    _msg: Option<Binary>,
+    msg: Option<Binary>,
 ) -> HandleResult {
-
-
+    // payloads meant for other contracts are rejected rather than forwarded with the default
+    // distribution, so a send to the wrong contract is reverted instead of split
+    let receive_msg: ReceiveMsg = msg
+        .map(|m| from_binary(&m))
+        .transpose()
+        .map_err(|_| StdError::generic_err(
+            "The send msg must be empty or only select a distribution or split_id",
+        ))?
+        .unwrap_or_default();
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    if receive_msg.hops >= config.max_forward_depth {
+        return Err(StdError::generic_err(
+            "Funds have been forwarded through too many contracts",
+        ));
+    }
    
     forward_funds(
         deps,
         env,
-        amount,      
+        from,
+        amount,
+        receive_msg,
         )
-
 }
 
 
 
+/// For forwarding native coins sent with the message
+pub fn forward_native<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    distribution: Option<String>,
+    split_id: Option<u64>,
+) -> HandleResult {
+    if env.message.sent_funds.is_empty() {
+        return Err(StdError::generic_err(
+            "No native coins were sent to forward",
+        ));
+    }
+
+    let msg = ReceiveMsg { distribution, split_id, hops: 0 };
+    let mut logs = action_logs("forward_native", &env);
+    logs.extend(selection_logs(&msg)?);
+    let (messages, native_logs, forwards) = native_payout_msgs(deps, &env, &msg)?;
+    logs.extend(native_logs);
+
+    Ok(HandleResponse {
+        messages,
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::ForwardFunds { forwards })?),
+    })
+}
+
 
 
 pub fn forward_funds<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    amount: Uint128
+    from: HumanAddr,
+    amount: Uint128,
+    msg: ReceiveMsg,
 ) -> StdResult<HandleResponse> {
-    
+    let snip20_address: HumanAddr = env.message.sender.clone();
+    let mut logs = action_logs("forward", &env);
+    logs.extend(selection_logs(&msg)?);
+    let (mut messages, token_logs, forward) =
+        distribute_token(deps, &env, snip20_address, &from, amount, &msg)?;
+    logs.extend(token_logs);
+    let mut forwards = vec![forward];
+
+    // native coins sent along with the snip20s
+    if !env.message.sent_funds.is_empty() {
+        let (native_messages, native_logs, native_forwards) = native_payout_msgs(deps, &env, &msg)?;
+        messages.extend(native_messages);
+        logs.extend(native_logs);
+        forwards.extend(native_forwards);
+    }
+
+
+    Ok(HandleResponse {
+        messages,
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::ForwardFunds { forwards })?),
+    })
+}
+
+/// Returns StdResult<(Vec<CosmosMsg>, Vec<LogAttribute>, ForwardSummary)> of the transfers that
+/// split an amount of a registered token according to its distribution, and the logs and
+/// summary describing the forward.  Contract recipients are sent their shares with Send, and
+/// forwarder payloads have their hop count raised by one.  In pull mode, the other shares are
+/// credited as claimable instead of transferred
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - a reference to the Env of contract's environment
+/// * `snip20_address` - address of the snip20 contract
+/// * `from` - a reference to the address the funds came from
+/// * `amount` - amount to split
+/// * `msg` - a reference to the ReceiveMsg selecting a distribution or split to use instead
+///           of the token's distribution
+fn distribute_token<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    snip20_address: HumanAddr,
+    from: &HumanAddr,
+    amount: Uint128,
+    msg: &ReceiveMsg,
+) -> StdResult<(Vec<CosmosMsg>, Vec<LogAttribute>, ForwardSummary)> {
     let mut msg_list: Vec<CosmosMsg> = vec![];
     let padding: Option<String> = None;
 
 
     // Finds hash associated with snip20 contract
-    let snip_contract_storage = ReadonlyPrefixedStorage::new(PREFIX_TOKEN_CONTRACT_INFO, &mut deps.storage);
-    let snip20_address: HumanAddr = env.message.sender;
-    let callback_code_wrapped: Option<String> = may_load(&snip_contract_storage, snip20_address.0.as_bytes())?;
-    let callback_code_hash: String;
+    let callback_code_hash: String = load_registered_token(&deps.storage, &snip20_address)?.code_hash;
+
+
     
-    if callback_code_wrapped == None {
+
+
+    //Payment distribution
+    let royalty_list = forward_dist(deps, &env.block, Some(&snip20_address), msg)?;
+    let split = royalty_list.split(amount.u128())?;
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let from_raw = deps.api.canonical_address(from)?;
+    let forward_id = record_forward(&mut deps.storage, env, snip20_address.as_str(), &from_raw, amount.u128(), &split, msg.split_id)?;
+    let logs = forward_logs(&deps.api, snip20_address.as_str(), from, amount.u128(), &royalty_list, &split)?;
+    let summary = forward_summary(&deps.api, config.hide_recipients, forward_id, snip20_address.as_str(), amount.u128(), &split)?;
+
+    // payouts are in the same order as the royalties
+    for (royalty, payout) in royalty_list.royalties.iter().zip(split.payouts.into_iter()) {
+        let recipient = deps.api.human_address(&payout.recipient)?;
+        if royalty.code_hash.is_some() {
+            // contract recipients can not claim, so they are always sent their share.  Sending
+            // nothing would still call their Receive
+            if payout.amount > 0 {
+                msg_list.push(send_msg(
+                    recipient,
+                    Uint128(payout.amount),
+                    next_hop_msg(royalty.msg.as_ref(), msg.hops)?,
+                    padding.clone(),
+                    BLOCK_SIZE,
+                    callback_code_hash.clone(),
+                    snip20_address.clone(),
+                )?);
+            }
+            continue;
+        }
+        match config.distribution_mode {
+            DistributionMode::Push => {
+                let cosmos_msg = transfer_msg(
+                    recipient,
+                    Uint128(payout.amount),
+                    padding.clone(),
+                    BLOCK_SIZE,
+                    callback_code_hash.clone(),
+                    snip20_address.clone(),
+                )?;
+                msg_list.push(cosmos_msg);
+            }
+            DistributionMode::Pull => {
+                credit_claimable(&mut deps.storage, &payout.recipient, &snip20_address, payout.amount)?;
+            }
+        }
+    }
+
+
+    Ok((msg_list, logs, summary))
+}
+
+/// Returns StdResult<Option<Binary>> of the payload to Send a contract recipient.  A payload
+/// another forwarder would accept, or the lack of one, carries the hop count of the received
+/// funds plus one, so a cycle of forwarders always ends.  Any other payload is sent unchanged,
+/// so other contracts are not sent fields they do not expect
+///
+/// # Arguments
+///
+/// * `royalty_msg` - optional reference to the msg the distribution sends the recipient
+/// * `hops` - number of forwarders the received funds have passed through
+fn next_hop_msg(royalty_msg: Option<&Binary>, hops: u32) -> StdResult<Option<Binary>> {
+    let bin = match royalty_msg {
+        Some(bin) => bin,
+        None => {
+            return Ok(Some(to_binary(&ReceiveMsg {
+                hops: hops.saturating_add(1),
+                ..ReceiveMsg::default()
+            })?))
+        }
+    };
+    match from_binary::<ReceiveMsg>(bin) {
+        Ok(next) => Ok(Some(to_binary(&ReceiveMsg {
+            hops: hops.saturating_add(1),
+            ..next
+        })?)),
+        Err(_) => Ok(Some(bin.clone())),
+    }
+}
+
+/// Returns StdResult<(Vec<CosmosMsg>, Vec<LogAttribute>, Vec<ForwardSummary>)> of the bank
+/// sends that split every native coin sent with the message according to the default
+/// distribution or the selected named distribution or split, with one send per recipient, and the logs and summaries describing the
+/// forward of each coin.  Native coins can not be claimed, so they are rejected in pull mode
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - a reference to the Env of contract's environment
+/// * `msg` - a reference to the ReceiveMsg selecting a distribution or split to use instead
+///           of the default distribution
+fn native_payout_msgs<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    msg: &ReceiveMsg,
+) -> StdResult<(Vec<CosmosMsg>, Vec<LogAttribute>, Vec<ForwardSummary>)> {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    if config.distribution_mode == DistributionMode::Pull {
         return Err(StdError::generic_err(
-            "This token is not registered with this contract. Please register it",
+            "Native coins can not be forwarded in pull mode, because they can not be claimed",
         ));
     }
+    let royalty_list = forward_dist(deps, &env.block, None, msg)?;
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    let mut coins_by_recipient: Vec<(CanonicalAddr, Vec<Coin>)> = Vec::new();
+    let mut logs: Vec<LogAttribute> = Vec::new();
+    let mut forwards: Vec<ForwardSummary> = Vec::new();
+
+    for sent in env.message.sent_funds.iter() {
+        let split = royalty_list.split(sent.amount.u128())?;
+        let forward_id = record_forward(&mut deps.storage, env, &sent.denom, &sender_raw, sent.amount.u128(), &split, msg.split_id)?;
+        logs.extend(forward_logs(&deps.api, &sent.denom, &env.message.sender, sent.amount.u128(), &royalty_list, &split)?);
+        forwards.push(forward_summary(&deps.api, config.hide_recipients, forward_id, &sent.denom, sent.amount.u128(), &split)?);
+        for payout in split.payouts.into_iter() {
+            // the bank module rejects zero coins
+            if payout.amount == 0 {
+                continue;
+            }
+            let idx = match coins_by_recipient.iter().position(|(r, _)| *r == payout.recipient) {
+                Some(idx) => idx,
+                None => {
+                    coins_by_recipient.push((payout.recipient, Vec::new()));
+                    coins_by_recipient.len() - 1
+                }
+            };
+            let coins = &mut coins_by_recipient[idx].1;
+            if let Some(coin) = coins.iter_mut().find(|c| c.denom == sent.denom) {
+                coin.amount = Uint128(coin.amount.u128() + payout.amount);
+            } else {
+                coins.push(Coin {
+                    denom: sent.denom.clone(),
+                    amount: Uint128(payout.amount),
+                });
+            }
+        }
+    }
+
+    let messages = coins_by_recipient
+        .into_iter()
+        .map(|(recipient, mut amount)| {
+            amount.sort_by(|a, b| a.denom.cmp(&b.denom));
+            Ok(CosmosMsg::Bank(BankMsg::Send {
+                from_address: env.contract.address.clone(),
+                to_address: deps.api.human_address(&recipient)?,
+                amount,
+            }))
+        })
+        .collect::<StdResult<Vec<CosmosMsg>>>()?;
+    Ok((messages, logs, forwards))
+}
+
+/// Returns StdResult<DistKey> of the distribution selected by an optional token address or
+/// distribution name, which can not both be given
+///
+/// # Arguments
+///
+/// * `token` - optional address of the snip20 contract
+/// * `distribution` - optional name of the distribution
+fn dist_key(token: Option<HumanAddr>, distribution: Option<String>) -> StdResult<DistKey> {
+    match (token, distribution) {
+        (Some(_), Some(_)) => Err(StdError::generic_err(
+            "Specify either a token or a named distribution, not both",
+        )),
+        (Some(addr), None) => Ok(DistKey::Token(addr)),
+        (None, Some(name)) => Ok(DistKey::Named(name)),
+        (None, None) => Ok(DistKey::Default),
+    }
+}
 
-    else {
-        callback_code_hash = callback_code_wrapped.unwrap();
+/// Returns Vec<LogAttribute> naming the token, named distribution or split a DistKey selects
+///
+/// # Arguments
+///
+/// * `key` - a reference to the key of the distribution
+fn dist_key_logs(key: &DistKey) -> Vec<LogAttribute> {
+    match key {
+        DistKey::Default => vec![],
+        DistKey::Token(addr) => vec![log("token", addr)],
+        DistKey::Named(name) => vec![log("distribution", name)],
+        DistKey::Split(split_id) => vec![log("split_id", split_id)],
     }
+}
 
+/// Returns StdResult<Vec<LogAttribute>> naming the distribution or split a ReceiveMsg selects,
+/// or an error if it selects both
+///
+/// # Arguments
+///
+/// * `msg` - a reference to the ReceiveMsg
+fn selection_logs(msg: &ReceiveMsg) -> StdResult<Vec<LogAttribute>> {
+    match (msg.distribution.as_ref(), msg.split_id) {
+        (Some(_), Some(_)) => Err(StdError::generic_err(
+            "Specify either a named distribution or a split, not both",
+        )),
+        (Some(name), None) => Ok(vec![log("distribution", name)]),
+        (None, Some(split_id)) => Ok(vec![log("split_id", split_id)]),
+        (None, None) => Ok(vec![]),
+    }
+}
 
-    
+/// Returns StdResult<StoredRoyaltyInfo> of the distribution funds are split with after applying
+/// any matured changes to it.  A selected split or named distribution is used if there is one,
+/// otherwise the token's own distribution, or the default distribution
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `block` - a reference to the current BlockInfo
+/// * `token` - optional address of the snip20 contract, None for native coins
+/// * `msg` - a reference to the ReceiveMsg selecting a distribution or split
+fn forward_dist<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    block: &BlockInfo,
+    token: Option<&HumanAddr>,
+    msg: &ReceiveMsg,
+) -> StdResult<StoredRoyaltyInfo> {
+    if let Some(split_id) = msg.split_id {
+        apply_matured_dist(deps, block, &DistKey::Split(split_id))?;
+        return Ok(load_split(&deps.storage, split_id)?.dist_info);
+    }
+    if let Some(name) = msg.distribution.as_ref() {
+        let key = DistKey::Named(name.clone());
+        apply_matured_dist(deps, block, &key)?;
+        return may_load_own_dist(&deps.storage, &key)?
+            .ok_or_else(|| StdError::generic_err(format!("There is no distribution named {}", name)));
+    }
+    apply_matured_dist(deps, block, &DistKey::Default)?;
+    match token {
+        Some(addr) => {
+            apply_matured_dist(deps, block, &DistKey::Token(addr.clone()))?;
+            load_dist_info(&deps.storage, addr)
+        }
+        None => load(&deps.storage, FUNDS_DISTRIBUTION_KEY),
+    }
+}
 
+/// Returns Vec<LogAttribute> of the action and its sender, which every handle logs first
+///
+/// # Arguments
+///
+/// * `action` - name of the action
+/// * `env` - a reference to the Env of contract's environment
+fn action_logs(action: &str, env: &Env) -> Vec<LogAttribute> {
+    vec![log("action", action), log("sender", &env.message.sender)]
+}
 
-    //Payment distribution
-    let royalty_list = load::<StoredRoyaltyInfo, _>(&deps.storage, FUNDS_DISTRIBUTION_KEY)?;
- 
-    for royalty in royalty_list.royalties.iter() {
-        let decimal_places : u32 = royalty_list.decimal_places_in_rates.into();
-        let rate :u128 = (royalty.rate as u128) * (10 as u128).pow(decimal_places);
-        let amount = Uint128((amount.u128() * rate) / (100 as u128).pow(decimal_places));
-        let recipient = deps.api.human_address(&royalty.recipient).unwrap();
-        let cosmos_msg = transfer_msg(
-            recipient,
-            amount,
-            padding.clone(),
-            BLOCK_SIZE,
-            callback_code_hash.clone(),
-            snip20_address.clone(),
-        )?;
-        msg_list.push(cosmos_msg);
+/// Returns StdResult<Vec<LogAttribute>> describing a forward
+///
+/// # Arguments
+///
+/// * `api` - a reference to the Api used to convert human and canonical addresses
+/// * `token` - snip20 contract address or native denom that was forwarded
+/// * `from` - a reference to the address the funds came from
+/// * `amount` - amount that was split
+/// * `dist` - a reference to the distribution used
+/// * `split` - a reference to the resulting Split
+fn forward_logs<A: Api>(
+    api: &A,
+    token: &str,
+    from: &HumanAddr,
+    amount: u128,
+    dist: &StoredRoyaltyInfo,
+    split: &Split,
+) -> StdResult<Vec<LogAttribute>> {
+    Ok(vec![
+        log("token", token),
+        log("from", from),
+        log("amount", amount),
+        log("recipient_count", split.payouts.len()),
+        log("dust", split.dust),
+        log("distribution_hash", dist_hash(api, Some(dist))?),
+    ])
+}
+
+/// Returns StdResult<ForwardSummary> describing a forward in a handle's answer
+///
+/// # Arguments
+///
+/// * `api` - a reference to the Api used to convert human and canonical addresses
+/// * `hide_addr` - true if recipient addresses should be kept hidden
+/// * `forward_id` - id of the forward in the ledger
+/// * `token` - snip20 contract address or native denom that was forwarded
+/// * `amount` - amount that was split
+/// * `split` - a reference to the resulting Split
+fn forward_summary<A: Api>(
+    api: &A,
+    hide_addr: bool,
+    forward_id: u32,
+    token: &str,
+    amount: u128,
+    split: &Split,
+) -> StdResult<ForwardSummary> {
+    Ok(ForwardSummary {
+        forward_id,
+        token: token.to_string(),
+        amount: Uint128(amount),
+        payouts: split
+            .payouts
+            .iter()
+            .map(|p| {
+                Ok(ForwardPayout {
+                    recipient: if hide_addr {
+                        None
+                    } else {
+                        Some(api.human_address(&p.recipient)?)
+                    },
+                    amount: Uint128(p.amount),
+                })
+            })
+            .collect::<StdResult<Vec<ForwardPayout>>>()?,
+        dust: Uint128(split.dust),
+    })
+}
+
+/// Returns StdResult<String> of the base64 encoded sha256 hash of a distribution's json, so
+/// indexers can tell when it changes.  A token using the default distribution is "default"
+///
+/// # Arguments
+///
+/// * `api` - a reference to the Api used to convert human and canonical addresses
+/// * `dist` - optional reference to the distribution
+fn dist_hash<A: Api>(api: &A, dist: Option<&StoredRoyaltyInfo>) -> StdResult<String> {
+    match dist {
+        Some(dist) => {
+            let json = to_binary(&dist.to_human_old(api)?)?;
+            Ok(Binary::from(&sha_256(json.as_slice())[..]).to_base64())
+        }
+        None => Ok(String::from("default")),
     }
+}
 
+/// Returns StdResult<u32> of the ledger id of a forward after appending it to the ledger and
+/// recording its payouts.  A split's forward is only appended to the split's own ledger, so it
+/// is left out of the contract's ledger, stats and recipient earnings
+///
+/// # Arguments
+///
+/// * `storage` - a mutable reference to the contract's storage
+/// * `env` - a reference to the Env of contract's environment
+/// * `token` - snip20 contract address or native denom that was forwarded
+/// * `from` - a reference to the address the funds came from
+/// * `amount` - amount that was split
+/// * `split` - a reference to the resulting Split
+/// * `split_id` - id of the split that was forwarded with, if any
+fn record_forward<S: Storage>(
+    storage: &mut S,
+    env: &Env,
+    token: &str,
+    from: &CanonicalAddr,
+    amount: u128,
+    split: &Split,
+    split_id: Option<u64>,
+) -> StdResult<u32> {
+    let forward = StoredForward {
+        token: token.to_string(),
+        from: from.clone(),
+        amount,
+        payouts: split.payouts.clone(),
+        dust: split.dust,
+        block_height: env.block.height,
+        block_time: env.block.time,
+    };
+    if let Some(split_id) = split_id {
+        let mut split_ledger_storage = PrefixedStorage::multilevel(&[PREFIX_SPLIT_LEDGER, &split_id.to_be_bytes()], storage);
+        let mut split_ledger = AppendStoreMut::<StoredForward, _>::attach_or_create(&mut split_ledger_storage)?;
+        let forward_id = split_ledger.len();
+        split_ledger.push(&forward)?;
+        return Ok(forward_id);
+    }
 
+    let mut ledger_storage = PrefixedStorage::new(PREFIX_FORWARD_LEDGER, storage);
+    let mut ledger = AppendStoreMut::<StoredForward, _>::attach_or_create(&mut ledger_storage)?;
+    let forward_id = ledger.len();
+    ledger.push(&forward)?;
 
+    let mut token_forwards_storage = PrefixedStorage::multilevel(&[PREFIX_TOKEN_FORWARDS, token.as_bytes()], storage);
+    let mut token_forwards = AppendStoreMut::<u32, _>::attach_or_create(&mut token_forwards_storage)?;
+    token_forwards.push(&forward_id)?;
 
+    record_stats(storage, env, token, amount, split)?;
+    record_payouts(storage, env, token, forward_id, &split.payouts)?;
+    Ok(forward_id)
+}
 
-    Ok(HandleResponse {
-        messages: msg_list,
-        log: vec![],
-        data: None,
-    })
+/// Returns StdResult<()> after adding a forward to the lifetime and current epoch totals of
+/// the token
+///
+/// # Arguments
+///
+/// * `storage` - a mutable reference to the contract's storage
+/// * `env` - a reference to the Env of contract's environment
+/// * `token` - snip20 contract address or native denom that was forwarded
+/// * `amount` - amount that was split
+/// * `split` - a reference to the resulting Split
+fn record_stats<S: Storage>(
+    storage: &mut S,
+    env: &Env,
+    token: &str,
+    amount: u128,
+    split: &Split,
+) -> StdResult<()> {
+    let config: Config = load(storage, CONFIG_KEY)?;
+    let epoch_key = (env.block.height / config.epoch_length).to_be_bytes();
+
+    let mut stats_storage = PrefixedStorage::new(PREFIX_TOKEN_STATS, storage);
+    let mut stats: StoredStats = may_load(&stats_storage, token.as_bytes())?.unwrap_or_default();
+    stats.add(amount, split.dust);
+    save(&mut stats_storage, token.as_bytes(), &stats)?;
+
+    let mut epoch_stats_storage = PrefixedStorage::multilevel(&[PREFIX_EPOCH_STATS, token.as_bytes()], storage);
+    let mut epoch_stats: StoredStats = may_load(&epoch_stats_storage, &epoch_key)?.unwrap_or_default();
+    epoch_stats.add(amount, split.dust);
+    save(&mut epoch_stats_storage, &epoch_key, &epoch_stats)?;
+
+    let mut paid_storage = PrefixedStorage::multilevel(&[PREFIX_EPOCH_PAID, token.as_bytes(), &epoch_key], storage);
+    for payout in split.payouts.iter().filter(|p| p.amount > 0) {
+        let paid: u128 = may_load(&paid_storage, payout.recipient.as_slice())?.unwrap_or(0);
+        save(&mut paid_storage, payout.recipient.as_slice(), &paid.saturating_add(payout.amount))?;
+    }
+    Ok(())
 }
 
-/// Calls register_receive a snip20 token contract
-/// and saves snip20 contract hash keyed to address
-/// 
+/// Returns StdResult<()> after adding payouts to each recipient's lifetime totals and
+/// payout history
+///
+/// # Arguments
+///
+/// * `storage` - a mutable reference to the contract's storage
+/// * `env` - a reference to the Env of contract's environment
+/// * `token` - snip20 contract address or native denom that was paid
+/// * `forward_id` - id of the forward in the ledger
+/// * `payouts` - the payouts to record
+fn record_payouts<S: Storage>(
+    storage: &mut S,
+    env: &Env,
+    token: &str,
+    forward_id: u32,
+    payouts: &[StoredPayout],
+) -> StdResult<()> {
+    for payout in payouts.iter().filter(|p| p.amount > 0) {
+        let mut earnings_storage = PrefixedStorage::multilevel(&[PREFIX_EARNINGS, payout.recipient.as_slice()], storage);
+        let earned: Option<u128> = may_load(&earnings_storage, token.as_bytes())?;
+        save(
+            &mut earnings_storage,
+            token.as_bytes(),
+            &earned.unwrap_or(0).saturating_add(payout.amount),
+        )?;
+
+        // first payment in this token
+        if earned.is_none() {
+            let mut earned_tokens_storage = PrefixedStorage::new(PREFIX_EARNED_TOKENS, storage);
+            let mut earned_tokens: Vec<String> =
+                may_load(&earned_tokens_storage, payout.recipient.as_slice())?.unwrap_or_default();
+            earned_tokens.push(token.to_string());
+            save(&mut earned_tokens_storage, payout.recipient.as_slice(), &earned_tokens)?;
+
+            let mut recipients_storage = PrefixedStorage::new(PREFIX_TOKEN_RECIPIENTS, storage);
+            let mut recipients: Vec<CanonicalAddr> =
+                may_load(&recipients_storage, token.as_bytes())?.unwrap_or_default();
+            recipients.push(payout.recipient.clone());
+            save(&mut recipients_storage, token.as_bytes(), &recipients)?;
+        }
+
+        let mut history_storage = PrefixedStorage::multilevel(&[PREFIX_PAYOUT_HISTORY, payout.recipient.as_slice()], storage);
+        let mut history = AppendStoreMut::<StoredPayoutRecord, _>::attach_or_create(&mut history_storage)?;
+        let position = history.len();
+        history.push(&StoredPayoutRecord {
+            token: token.to_string(),
+            amount: payout.amount,
+            block_height: env.block.height,
+            block_time: env.block.time,
+            forward_id,
+        })?;
+
+        let mut token_history_storage = PrefixedStorage::multilevel(
+            &[PREFIX_TOKEN_PAYOUT_HISTORY, payout.recipient.as_slice(), token.as_bytes()],
+            storage,
+        );
+        let mut token_history = AppendStoreMut::<u32, _>::attach_or_create(&mut token_history_storage)?;
+        token_history.push(&position)?;
+    }
+    Ok(())
+}
+
+/// Returns StdResult<()> after adding to a recipient's claimable balance of a token
+///
+/// # Arguments
+///
+/// * `storage` - a mutable reference to the contract's storage
+/// * `recipient` - a reference to the recipient's address
+/// * `token` - a reference to the address of the snip20 contract
+/// * `amount` - amount to add
+fn credit_claimable<S: Storage>(
+    storage: &mut S,
+    recipient: &CanonicalAddr,
+    token: &HumanAddr,
+    amount: u128,
+) -> StdResult<()> {
+    let mut claimable_storage = PrefixedStorage::multilevel(&[PREFIX_CLAIMABLE, recipient.as_slice()], storage);
+    let balance: u128 = may_load(&claimable_storage, token.0.as_bytes())?.unwrap_or(0);
+    let new_balance = balance.checked_add(amount).ok_or_else(|| {
+        StdError::generic_err("Claimable balance would overflow")
+    })?;
+    save(&mut claimable_storage, token.0.as_bytes(), &new_balance)?;
+
+    // the total can not overflow if no individual balance does, as the contract holds it all
+    let total = total_claimable(&*storage, token)?;
+    let mut total_storage = PrefixedStorage::new(PREFIX_TOTAL_CLAIMABLE, storage);
+    save(&mut total_storage, token.0.as_bytes(), &(total.saturating_add(amount)))
+}
+
+/// Returns StdResult<u128> of the total claimable balance of a token, owed to all recipients
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `token` - a reference to the address of the snip20 contract
+fn total_claimable<S: ReadonlyStorage>(storage: &S, token: &HumanAddr) -> StdResult<u128> {
+    let total_storage = ReadonlyPrefixedStorage::new(PREFIX_TOTAL_CLAIMABLE, storage);
+    Ok(may_load(&total_storage, token.0.as_bytes())?.unwrap_or(0))
+}
+
+/// Withdraws the sender's claimable balances of the listed tokens
+///
 /// # Arguements
 /// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
 /// * `env` - Env of contract's environment
-/// * `snip20_addr` - address of the snip20 contract to be registered
-/// * `snip20_hash` - contract callback hash of the snip20 contract
-pub fn register_token<S: Storage, A: Api, Q: Querier>(
+/// * `tokens` - addresses of the snip20 contracts to claim
+pub fn claim<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    snip20_addr: HumanAddr,
-    snip20_hash: String
+    tokens: Vec<HumanAddr>,
 ) -> StdResult<HandleResponse> {
-
-    let config: Config = load(&deps.storage, CONFIG_KEY)?;  
     let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    let mut msg_list: Vec<CosmosMsg> = vec![];
+    let mut logs = action_logs("claim", &env);
+    let mut claimed: Vec<TokenBalance> = Vec::new();
+
+    for token in tokens.into_iter() {
+        let mut claimable_storage = PrefixedStorage::multilevel(&[PREFIX_CLAIMABLE, sender_raw.as_slice()], &mut deps.storage);
+        let balance: u128 = may_load(&claimable_storage, token.0.as_bytes())?.unwrap_or(0);
+        if balance == 0 {
+            continue;
+        }
+        remove(&mut claimable_storage, token.0.as_bytes());
+        let total = total_claimable(&deps.storage, &token)?;
+        let mut total_storage = PrefixedStorage::new(PREFIX_TOTAL_CLAIMABLE, &mut deps.storage);
+        save(&mut total_storage, token.0.as_bytes(), &(total.saturating_sub(balance)))?;
+
+        let callback_code_hash: String = may_load_token_info(&deps.storage, &token)?
+            .ok_or_else(|| StdError::generic_err(format!("{} is not a registered token", token)))?
+            .code_hash;
+        logs.push(log("token", &token));
+        logs.push(log("amount", balance));
+        claimed.push(TokenBalance {
+            token: token.clone(),
+            amount: Uint128(balance),
+        });
+        msg_list.push(transfer_msg(
+            env.message.sender.clone(),
+            Uint128(balance),
+            None,
+            BLOCK_SIZE,
+            callback_code_hash,
+            token,
+        )?);
+    }
 
-
-    
-    if config.admin != sender_raw {
+    if msg_list.is_empty() {
         return Err(StdError::generic_err(
-            "This function is only usable by the Admin",
+            "There is nothing to claim for the listed tokens",
         ));
     }
 
-
-    let mut snip_contract_storage = PrefixedStorage::new(PREFIX_TOKEN_CONTRACT_INFO, &mut deps.storage);
-    
-    save(&mut snip_contract_storage, snip20_addr.0.as_bytes(), &snip20_hash)?;
-
-
     Ok(HandleResponse {
-        messages: vec![
-            register_receive_msg(
-                env.contract_code_hash,
-                None,
-                BLOCK_SIZE,
-                snip20_hash,
-                snip20_addr
-            )?
-        ],
-        log: vec![],
-        data: None,
+        messages: msg_list,
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::Claim { claimed })?),
     })
 }
 
-
-
-
-pub fn change_dist<S: Storage, A: Api, Q: Querier>(
+/// Performs an admin action if the sender is an admin and no other approvals are needed,
+/// otherwise records it as a proposal approved by the sender
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `action` - the admin action to perform
+pub fn admin_action<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    dist_info: RoyaltyInfo,
+    action: AdminAction,
 ) -> StdResult<HandleResponse> {
-    let config: Config = load(&deps.storage, CONFIG_KEY)?;  
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
     let sender_raw = deps.api.canonical_address(&env.message.sender)?;
 
-    if config.admin != sender_raw {
+    if !config.admins.contains(&sender_raw) {
         return Err(StdError::generic_err(
             "This function is only usable by the Admin",
         ));
     }
 
-    store_dist_info(
-        &mut deps.storage,
-        &deps.api,
-        Some(dist_info).as_ref(),
-        None,
-        FUNDS_DISTRIBUTION_KEY
-    )?;
-    
+    if config.threshold <= 1 {
+        return execute_admin_action(deps, env, action);
+    }
+
+    let proposal_id: u64 = may_load(&deps.storage, PROPOSAL_COUNT_KEY)?.unwrap_or(0);
+    save(&mut deps.storage, PROPOSAL_COUNT_KEY, &(proposal_id + 1))?;
+    let mut logs = action_logs("admin_proposal", &env);
+    logs.push(log("proposed_action", action.name()));
+    logs.push(log("proposal_id", proposal_id));
+    logs.push(log("approvals", 1));
+    let proposal = StoredProposal {
+        action,
+        approvals: vec![sender_raw],
+        executed: false,
+        created_height: env.block.height,
+    };
+    let mut proposal_storage = PrefixedStorage::new(PREFIX_PROPOSALS, &mut deps.storage);
+    save(&mut proposal_storage, &proposal_id.to_be_bytes(), &proposal)?;
 
 
-    Ok(HandleResponse::default())
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::Proposal {
+            proposal_id,
+            approvals: 1,
+        })?),
+    })
 }
 
 
-pub fn change_admin<S: Storage, A: Api, Q: Querier>(
+/// Approves an admin proposal, and performs its action once enough admins have approved
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `proposal_id` - id of the proposal to approve
+pub fn approve_proposal<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    admin_addr: HumanAddr,
+    proposal_id: u64,
 ) -> StdResult<HandleResponse> {
-    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;  
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
     let sender_raw = deps.api.canonical_address(&env.message.sender)?;
 
-    if config.admin != sender_raw {
+    if !config.admins.contains(&sender_raw) {
         return Err(StdError::generic_err(
             "This function is only usable by the Admin",
         ));
     }
 
-    config.admin = deps.api.canonical_address(&admin_addr)?;
+    let mut proposal_storage = PrefixedStorage::new(PREFIX_PROPOSALS, &mut deps.storage);
+    let mut proposal: StoredProposal = may_load(&proposal_storage, &proposal_id.to_be_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("Proposal {} does not exist", proposal_id)))?;
+    if proposal.executed {
+        return Err(StdError::generic_err(
+            "This proposal has already been executed",
+        ));
+    }
+    if proposal.approvals.contains(&sender_raw) {
+        return Err(StdError::generic_err(
+            "You have already approved this proposal",
+        ));
+    }
+    proposal.approvals.push(sender_raw);
+
+    // approvals of anyone no longer an admin do not count
+    let approvals = proposal
+        .approvals
+        .iter()
+        .filter(|a| config.admins.contains(*a))
+        .count() as u16;
+    proposal.executed = approvals >= config.threshold;
+    save(&mut proposal_storage, &proposal_id.to_be_bytes(), &proposal)?;
+
+    if proposal.executed {
+        let mut response = execute_admin_action(deps, env, proposal.action)?;
+        response.log.push(log("proposal_id", proposal_id));
+        return Ok(response);
+    }
 
-    save(&mut deps.storage, CONFIG_KEY, &config)?;
+    let mut logs = action_logs("approve_proposal", &env);
+    logs.push(log("proposal_id", proposal_id));
+    logs.push(log("approvals", approvals));
 
 
-    Ok(HandleResponse::default())
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::Proposal {
+            proposal_id,
+            approvals,
+        })?),
+    })
 }
 
 
-
-/// Returns StdResult<()>
+/// Performs an admin action that has been authorized
 ///
-/// verifies the royalty information is valid and if so, stores the royalty info for the token
-/// or as default
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `action` - the admin action to perform
+fn execute_admin_action<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    action: AdminAction,
+) -> StdResult<HandleResponse> {
+    match action {
+        AdminAction::RegisterToken { snip20_addr, snip20_hash, dist_info } => register_token(deps, env, snip20_addr, snip20_hash, dist_info),
+        AdminAction::SetTokenDistribution { snip20_addr, dist_info } => set_token_dist(deps, env, snip20_addr, dist_info),
+        AdminAction::ChangeDistribution { dist_info } => change_dist(deps, env, dist_info),
+        AdminAction::SetNamedDistribution { name, dist_info } => set_named_dist(deps, env, name, dist_info),
+        AdminAction::ProposeAdmin { admin_addr } => propose_admin(deps, env, admin_addr),
+        AdminAction::CancelAdminProposal {} => cancel_admin_proposal(deps, env),
+        AdminAction::ChangeAdmins { admins, threshold } => change_admins(deps, env, admins, threshold),
+        AdminAction::SetDistributionMode { mode } => set_dist_mode(deps, env, mode),
+        AdminAction::SetHideRecipients { hide_recipients } => set_hide_recipients(deps, env, hide_recipients),
+        AdminAction::SetTimelock { timelock } => set_timelock(deps, env, timelock),
+        AdminAction::CancelDistributionChange { token, distribution } => cancel_dist_change(deps, env, token, distribution),
+        AdminAction::LockRecipients {} => lock_recipients(deps, env),
+        AdminAction::SetRegistrationPolicy { policy } => set_registration_policy(deps, env, policy),
+        AdminAction::SetMaxForwardDepth { max_forward_depth } => set_max_forward_depth(deps, env, max_forward_depth),
+        AdminAction::UnregisterToken { snip20_addr } => unregister_token(deps, env, snip20_addr),
+        AdminAction::Sweep { token, amount, recipient } => sweep(deps, env, token, amount, recipient),
+        AdminAction::SweepNative { amount, recipient } => sweep_native(deps, env, amount, recipient),
+        AdminAction::RedistributeBalance { token } => redistribute_balance(deps, env, token),
+        AdminAction::SetTokenViewingKey { token, key } => set_token_key(deps, env, token, key),
+    }
+}
+
+/// Registers a token as an admin action if the sender is an admin, otherwise registers it if
+/// the registration policy allows anyone to
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `snip20_addr` - address of the snip20 contract to be registered
+/// * `snip20_hash` - contract callback hash of the snip20 contract
+/// * `dist_info` - optional distribution to use for this token instead of the default
+pub fn try_register_token<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    snip20_addr: HumanAddr,
+    snip20_hash: String,
+    dist_info: Option<RoyaltyInfo>,
+) -> StdResult<HandleResponse> {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    if config.admins.contains(&sender_raw) {
+        return admin_action(deps, env, AdminAction::RegisterToken { snip20_addr, snip20_hash, dist_info });
+    }
+
+    if !config.registration_policy.allows(&snip20_hash) {
+        return Err(StdError::generic_err(
+            "Registering this token is only usable by the Admin",
+        ));
+    }
+    if dist_info.is_some() {
+        return Err(StdError::generic_err(
+            "Only the Admin can give a token its own distribution",
+        ));
+    }
+    if load_registered_token(&deps.storage, &snip20_addr).is_ok() {
+        return Err(StdError::generic_err(
+            "This token is already registered",
+        ));
+    }
+    let registered: Vec<HumanAddr> = may_load(&deps.storage, REGISTERED_TOKENS_KEY)?.unwrap_or_default();
+    if registered.len() >= MAX_OPEN_REGISTRATIONS && !registered.contains(&snip20_addr) {
+        return Err(StdError::generic_err(format!(
+            "Only the Admin can register more than {} tokens",
+            MAX_OPEN_REGISTRATIONS
+        )));
+    }
+
+    register_token(deps, env, snip20_addr, snip20_hash, None)
+}
+
+
+/// Calls register_receive a snip20 token contract
+/// and saves snip20 contract hash keyed to address.  Registering a token again updates its hash
+/// 
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `snip20_addr` - address of the snip20 contract to be registered
+/// * `snip20_hash` - contract callback hash of the snip20 contract
+/// * `dist_info` - optional distribution to use for this token instead of the default
+pub fn register_token<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    snip20_addr: HumanAddr,
+    snip20_hash: String,
+    dist_info: Option<RoyaltyInfo>,
+) -> StdResult<HandleResponse> {
+
+    // confirms the address and hash belong to a snip20 before accepting it
+    let snip20_info = fetch_token_info(&deps.querier, &snip20_addr, &snip20_hash)?;
+    let registered_height = match may_load_token_info(&deps.storage, &snip20_addr)? {
+        Some(info) if info.registered => info.registered_height,
+        _ => env.block.height,
+    };
+    let token_info = StoredTokenInfo {
+        code_hash: snip20_hash.clone(),
+        name: snip20_info.name,
+        symbol: snip20_info.symbol.clone(),
+        decimals: snip20_info.decimals,
+        registered_height,
+        registered: true,
+    };
+    let mut snip_contract_storage = PrefixedStorage::new(PREFIX_TOKEN_CONTRACT_INFO, &mut deps.storage);
+    
+    save(&mut snip_contract_storage, snip20_addr.0.as_bytes(), &token_info)?;
+    let mut registered: Vec<HumanAddr> = may_load(&deps.storage, REGISTERED_TOKENS_KEY)?.unwrap_or_default();
+    if !registered.contains(&snip20_addr) {
+        registered.push(snip20_addr.clone());
+        save(&mut deps.storage, REGISTERED_TOKENS_KEY, &registered)?;
+    }
+
+    let key_msg = token_key_msg(&mut deps.storage, &env, &snip20_addr, &snip20_hash)?;
+
+    let mut logs = action_logs("register_token", &env);
+    logs.push(log("token", &snip20_addr));
+    logs.push(log("symbol", snip20_info.symbol));
+    let mut answer = None;
+    if let Some(dist_info) = dist_info.as_ref() {
+        logs.push(log("distribution_hash", dist_hash(&deps.api, Some(&validate_dist_info(&deps.api, &deps.api.canonical_address(&env.contract.address)?, dist_info)?))?));
+        answer = update_dist(deps, &env, &DistKey::Token(snip20_addr.clone()), Some(dist_info))?;
+    }
+
+
+    Ok(HandleResponse {
+        messages: vec![
+            register_receive_msg(
+                env.contract_code_hash,
+                None,
+                BLOCK_SIZE,
+                snip20_hash,
+                snip20_addr
+            )?,
+            key_msg,
+        ],
+        log: logs,
+        data: Some(to_binary(&answer.unwrap_or(HandleAnswer::RegisterToken {
+            status: ResponseStatus::Success,
+        }))?),
+    })
+}
+
+
+
+
+/// Stops accepting a token.  Its distribution is kept in case it is registered again, and
+/// its claimable balances can still be withdrawn
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `snip20_addr` - address of the registered snip20 contract
+pub fn unregister_token<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    snip20_addr: HumanAddr,
+) -> StdResult<HandleResponse> {
+    let mut token_info = load_registered_token(&deps.storage, &snip20_addr)?;
+    token_info.registered = false;
+    let mut snip_contract_storage = PrefixedStorage::new(PREFIX_TOKEN_CONTRACT_INFO, &mut deps.storage);
+    save(&mut snip_contract_storage, snip20_addr.0.as_bytes(), &token_info)?;
+
+    let mut logs = action_logs("unregister_token", &env);
+    logs.push(log("token", &snip20_addr));
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::UnregisterToken {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+
+
+/// Sends tokens held by the contract to a recipient.  If recipients are owed any of the token,
+/// the contract's balance is checked so the amount owed is not swept
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `token` - address of the snip20 contract
+/// * `amount` - amount to send
+/// * `recipient` - address to send the tokens to
+pub fn sweep<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    token: HumanAddr,
+    amount: Uint128,
+    recipient: HumanAddr,
+) -> StdResult<HandleResponse> {
+    let token_info = may_load_token_info(&deps.storage, &token)?
+        .ok_or_else(|| StdError::generic_err(format!("{} is not a registered token", token)))?;
+    let owed = total_claimable(&deps.storage, &token)?;
+    if owed > 0 {
+        let balance = contract_balance(deps, &env.contract.address, &token, &token_info)?;
+        if amount.u128() > balance.saturating_sub(owed) {
+            return Err(StdError::generic_err(
+                "Sweeping this amount would take funds owed to recipients",
+            ));
+        }
+    }
+
+    let mut logs = action_logs("sweep", &env);
+    logs.push(log("token", &token));
+    logs.push(log("amount", amount));
+    logs.push(log("recipient", &recipient));
+
+
+    Ok(HandleResponse {
+        messages: vec![transfer_msg(
+            recipient,
+            amount,
+            None,
+            BLOCK_SIZE,
+            token_info.code_hash,
+            token,
+        )?],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::Sweep {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+/// Sends native coins held by the contract to a recipient
+///
+/// # Arguements
+/// * `_deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `amount` - coins to send
+/// * `recipient` - address to send the coins to
+pub fn sweep_native<S: Storage, A: Api, Q: Querier>(
+    _deps: &mut Extern<S, A, Q>,
+    env: Env,
+    amount: Vec<Coin>,
+    recipient: HumanAddr,
+) -> StdResult<HandleResponse> {
+    if amount.iter().all(|c| c.amount.u128() == 0) {
+        return Err(StdError::generic_err(
+            "No native coins were specified to sweep",
+        ));
+    }
+
+    let mut logs = action_logs("sweep_native", &env);
+    let coins: Vec<String> = amount.iter().map(|c| format!("{}{}", c.amount, c.denom)).collect();
+    logs.push(log("amount", coins.join(",")));
+    logs.push(log("recipient", &recipient));
+
+
+    Ok(HandleResponse {
+        messages: vec![CosmosMsg::Bank(BankMsg::Send {
+            from_address: env.contract.address,
+            to_address: recipient,
+            amount,
+        })],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::SweepNative {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+/// Splits the contract's balance of a token, less any amounts owed to recipients, using the
+/// token's distribution
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `token` - address of the registered snip20 contract
+pub fn redistribute_balance<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    token: HumanAddr,
+) -> StdResult<HandleResponse> {
+    let token_info = load_registered_token(&deps.storage, &token)?;
+    let balance = contract_balance(deps, &env.contract.address, &token, &token_info)?;
+    let available = balance.saturating_sub(total_claimable(&deps.storage, &token)?);
+    if available == 0 {
+        return Err(StdError::generic_err(
+            "There is no balance of this token to redistribute",
+        ));
+    }
+
+
+    let mut logs = action_logs("redistribute_balance", &env);
+    let (messages, token_logs, forward) = distribute_token(deps, &env, token, &env.contract.address, Uint128(available), &ReceiveMsg::default())?;
+    logs.extend(token_logs);
+
+
+    Ok(HandleResponse {
+        messages,
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::RedistributeBalance { forward })?),
+    })
+}
+
+
+/// Replaces the viewing key the contract uses to query its balance of a token
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `token` - address of the registered snip20 contract
+/// * `key` - the viewing key
+pub fn set_token_key<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    token: HumanAddr,
+    key: String,
+) -> StdResult<HandleResponse> {
+    let token_info = load_registered_token(&deps.storage, &token)?;
+    let mut key_storage = PrefixedStorage::new(PREFIX_TOKEN_VIEWING_KEY, &mut deps.storage);
+    save(&mut key_storage, token.0.as_bytes(), &key)?;
+
+    let mut logs = action_logs("set_token_viewing_key", &env);
+    logs.push(log("token", &token));
+
+
+    Ok(HandleResponse {
+        messages: vec![set_viewing_key_msg(
+            key,
+            None,
+            BLOCK_SIZE,
+            token_info.code_hash,
+            token,
+        )?],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::SetTokenViewingKey {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+/// Sets or removes the distribution used for a registered token
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `snip20_addr` - address of the registered snip20 contract
+/// * `dist_info` - distribution to use for this token.  If None, the token will use the default
+pub fn set_token_dist<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    snip20_addr: HumanAddr,
+    dist_info: Option<RoyaltyInfo>,
+) -> StdResult<HandleResponse> {
+    load_registered_token(&deps.storage, &snip20_addr)?;
+
+    let mut logs = action_logs("set_token_distribution", &env);
+    logs.push(log("token", &snip20_addr));
+    let stored = dist_info.as_ref().map(|d| validate_dist_info(&deps.api, &deps.api.canonical_address(&env.contract.address)?, d)).transpose()?;
+    logs.push(log("distribution_hash", dist_hash(&deps.api, stored.as_ref())?));
+    let answer = update_dist(deps, &env, &DistKey::Token(snip20_addr.clone()), dist_info.as_ref())?;
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&answer.unwrap_or(HandleAnswer::SetTokenDistribution {
+            status: ResponseStatus::Success,
+        }))?),
+    })
+}
+
+
+pub fn change_dist<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    dist_info: RoyaltyInfo,
+) -> StdResult<HandleResponse> {
+    let mut logs = action_logs("change_distribution", &env);
+    let stored = validate_dist_info(&deps.api, &deps.api.canonical_address(&env.contract.address)?, &dist_info)?;
+    logs.push(log("distribution_hash", dist_hash(&deps.api, Some(&stored))?));
+    let answer = update_dist(deps, &env, &DistKey::Default, Some(&dist_info))?;
+    
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&answer.unwrap_or(HandleAnswer::ChangeDistribution {
+            status: ResponseStatus::Success,
+        }))?),
+    })
+}
+
+
+/// Sets or removes a named distribution that Receive msgs can select
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `name` - name of the distribution
+/// * `dist_info` - the new distribution, or None to remove it
+pub fn set_named_dist<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    name: String,
+    dist_info: Option<RoyaltyInfo>,
+) -> StdResult<HandleResponse> {
+    if name.is_empty() || name.len() > MAX_DISTRIBUTION_NAME_LEN {
+        return Err(StdError::generic_err(format!(
+            "Distribution names must be 1 to {} bytes long",
+            MAX_DISTRIBUTION_NAME_LEN
+        )));
+    }
+    let key = DistKey::Named(name.clone());
+    if dist_info.is_none() {
+        apply_matured_dist(deps, &env.block, &key)?;
+        if may_load_own_dist(&deps.storage, &key)?.is_none() {
+            return Err(StdError::generic_err(format!("There is no distribution named {}", name)));
+        }
+    }
+
+    let mut logs = action_logs("set_named_distribution", &env);
+    logs.push(log("distribution", &name));
+    let stored = dist_info.as_ref().map(|d| validate_dist_info(&deps.api, &deps.api.canonical_address(&env.contract.address)?, d)).transpose()?;
+    logs.push(log("distribution_hash", dist_hash(&deps.api, stored.as_ref())?));
+    let answer = update_dist(deps, &env, &key, dist_info.as_ref())?;
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&answer.unwrap_or(HandleAnswer::SetNamedDistribution {
+            status: ResponseStatus::Success,
+        }))?),
+    })
+}
+
+
+/// Creates a split with its own admin and distribution
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `name` - name of the split
+/// * `dist_info` - the split's distribution
+/// * `admin` - optional admin of the split.  Defaults to the sender
+pub fn create_split<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    name: String,
+    dist_info: RoyaltyInfo,
+    admin: Option<HumanAddr>,
+) -> StdResult<HandleResponse> {
+    if name.is_empty() || name.len() > MAX_SPLIT_NAME_LEN {
+        return Err(StdError::generic_err(format!(
+            "Split names must be 1 to {} bytes long",
+            MAX_SPLIT_NAME_LEN
+        )));
+    }
+    let admin = admin.unwrap_or_else(|| env.message.sender.clone());
+    let split = StoredSplit {
+        name,
+        admin: deps.api.canonical_address(&admin)?,
+        pending_admin: None,
+        dist_info: validate_dist_info(&deps.api, &deps.api.canonical_address(&env.contract.address)?, &dist_info)?,
+        created_height: env.block.height,
+    };
+    let split_id: u64 = may_load(&deps.storage, SPLIT_COUNT_KEY)?.unwrap_or(0);
+    save(&mut deps.storage, SPLIT_COUNT_KEY, &(split_id + 1))?;
+    save_split(&mut deps.storage, split_id, &split)?;
+
+    let mut logs = action_logs("create_split", &env);
+    logs.push(log("split_id", split_id));
+    logs.push(log("name", &split.name));
+    logs.push(log("admin", &admin));
+    logs.push(log("distribution_hash", dist_hash(&deps.api, Some(&split.dist_info))?));
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::CreateSplit { split_id })?),
+    })
+}
+
+
+/// Changes a split's distribution.  Like the contract's own distributions, the change waits for
+/// the timelock and needs the consent of locked recipients whose rates it lowers
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `split_id` - id of the split
+/// * `dist_info` - the split's new distribution
+pub fn change_split_dist<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    split_id: u64,
+    dist_info: RoyaltyInfo,
+) -> StdResult<HandleResponse> {
+    load_split_as_admin(deps, &env, split_id)?;
+
+    let mut logs = action_logs("change_split_distribution", &env);
+    logs.push(log("split_id", split_id));
+    let stored = validate_dist_info(&deps.api, &deps.api.canonical_address(&env.contract.address)?, &dist_info)?;
+    logs.push(log("distribution_hash", dist_hash(&deps.api, Some(&stored))?));
+    let answer = update_dist(deps, &env, &DistKey::Split(split_id), Some(&dist_info))?;
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&answer.unwrap_or(HandleAnswer::ChangeSplitDistribution {
+            status: ResponseStatus::Success,
+        }))?),
+    })
+}
+
+
+/// Proposes a new admin for a split, who must accept before becoming its admin
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `split_id` - id of the split
+/// * `admin_addr` - address proposed as the split's new admin
+pub fn propose_split_admin<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    split_id: u64,
+    admin_addr: HumanAddr,
+) -> StdResult<HandleResponse> {
+    let mut split = load_split_as_admin(deps, &env, split_id)?;
+
+    split.pending_admin = Some(deps.api.canonical_address(&admin_addr)?);
+
+    save_split(&mut deps.storage, split_id, &split)?;
+
+    let mut logs = action_logs("propose_split_admin", &env);
+    logs.push(log("split_id", split_id));
+    logs.push(log("new_admin", &admin_addr));
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::ProposeSplitAdmin {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+/// Makes the sender a split's admin if they were proposed
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `split_id` - id of the split
+pub fn accept_split_admin<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    split_id: u64,
+) -> StdResult<HandleResponse> {
+    let mut split = load_split(&deps.storage, split_id)?;
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+
+    if split.pending_admin.as_ref() != Some(&sender_raw) {
+        return Err(StdError::generic_err(
+            "This function is only usable by the proposed admin of the split",
+        ));
+    }
+
+    split.admin = sender_raw;
+    split.pending_admin = None;
+
+    save_split(&mut deps.storage, split_id, &split)?;
+
+    let mut logs = action_logs("accept_split_admin", &env);
+    logs.push(log("split_id", split_id));
+    logs.push(log("new_admin", &env.message.sender));
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::AcceptSplitAdmin {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+/// Proposes a new admin, who must accept before becoming admin
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `admin_addr` - address proposed as the new admin
+pub fn propose_admin<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    admin_addr: HumanAddr,
+) -> StdResult<HandleResponse> {
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+
+    config.pending_admin = Some(deps.api.canonical_address(&admin_addr)?);
+
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    let mut logs = action_logs("propose_admin", &env);
+    logs.push(log("new_admin", &admin_addr));
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::ProposeAdmin {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+/// Makes the sender the sole admin if they were proposed
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+pub fn accept_admin<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;  
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+
+    if config.pending_admin.as_ref() != Some(&sender_raw) {
+        return Err(StdError::generic_err(
+            "This function is only usable by the proposed admin",
+        ));
+    }
+
+    config.admins = vec![sender_raw];
+    config.threshold = 1;
+    config.pending_admin = None;
+
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    let mut logs = action_logs("accept_admin", &env);
+    logs.push(log("new_admin", &env.message.sender));
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::AcceptAdmin {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+pub fn cancel_admin_proposal<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    if config.pending_admin.is_none() {
+        return Err(StdError::generic_err(
+            "There is no pending admin proposal",
+        ));
+    }
+
+    config.pending_admin = None;
+
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    let logs = action_logs("cancel_admin_proposal", &env);
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::CancelAdminProposal {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+
+/// Replaces the admins and the number of them that must approve admin actions
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `admins` - the new admins
+/// * `threshold` - number of admins that must approve an admin action
+pub fn change_admins<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    admins: Vec<HumanAddr>,
+    threshold: u16,
+) -> StdResult<HandleResponse> {
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+
+    config.admins = validate_admins(&deps.api, &admins, threshold)?;
+    config.threshold = threshold;
+    config.pending_admin = None;
+
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    let mut logs = action_logs("change_admins", &env);
+    let admin_list: Vec<&str> = admins.iter().map(|a| a.as_str()).collect();
+    logs.push(log("admins", admin_list.join(",")));
+    logs.push(log("threshold", threshold));
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::ChangeAdmins {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+/// Returns StdResult<Vec<CanonicalAddr>> of the admins if they are unique and the threshold
+/// can be met
+///
+/// # Arguments
+///
+/// * `api` - a reference to the Api used to convert human and canonical addresses
+/// * `admins` - the admin addresses
+/// * `threshold` - number of admins that must approve an admin action
+fn validate_admins<A: Api>(api: &A, admins: &[HumanAddr], threshold: u16) -> StdResult<Vec<CanonicalAddr>> {
+    let mut admins_raw: Vec<CanonicalAddr> = Vec::new();
+    for admin in admins.iter() {
+        let admin_raw = api.canonical_address(admin)?;
+        if admins_raw.contains(&admin_raw) {
+            return Err(StdError::generic_err(format!("{} is listed as an admin more than once", admin)));
+        }
+        admins_raw.push(admin_raw);
+    }
+    if threshold == 0 || threshold as usize > admins_raw.len() {
+        return Err(StdError::generic_err(
+            "The admin threshold must be between 1 and the number of admins",
+        ));
+    }
+    Ok(admins_raw)
+}
+
+
+
+pub fn set_dist_mode<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    mode: DistributionMode,
+) -> StdResult<HandleResponse> {
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+
+    config.distribution_mode = mode;
+
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    let mut logs = action_logs("set_distribution_mode", &env);
+    logs.push(log("mode", match mode {
+        DistributionMode::Push => "push",
+        DistributionMode::Pull => "pull",
+    }));
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::SetDistributionMode {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+
+pub fn set_hide_recipients<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    hide_recipients: bool,
+) -> StdResult<HandleResponse> {
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+
+    config.hide_recipients = hide_recipients;
+
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    let mut logs = action_logs("set_hide_recipients", &env);
+    logs.push(log("hide_recipients", hide_recipients));
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::SetHideRecipients {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+/// Sets the delay before distribution changes take effect.  A shorter delay is scheduled to
+/// take effect once the current delay has passed, so it can not be used to rush a change
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `timelock` - the new delay, or None for no delay
+pub fn set_timelock<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    timelock: Option<Timelock>,
+) -> StdResult<HandleResponse> {
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    apply_matured_timelock(&mut config, &env.block);
+
+    if Timelock::is_no_shorter(&timelock, &config.timelock) {
+        config.timelock = timelock;
+        config.pending_timelock = None;
+    } else if let Some(current) = config.timelock {
+        config.pending_timelock = Some(PendingTimelock {
+            timelock,
+            activation: current.activation(&env.block),
+        });
+    }
+
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    let logs = action_logs("set_timelock", &env);
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::SetTimelock {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+/// Cancels a distribution change that has not yet taken effect
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `token` - address of the snip20 contract whose change is cancelled, or None for the default
+/// * `distribution` - name of the distribution whose change is cancelled
+pub fn cancel_dist_change<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    token: Option<HumanAddr>,
+    distribution: Option<String>,
+) -> StdResult<HandleResponse> {
+    let key = dist_key(token, distribution)?;
+    match may_load_pending_dist(&deps.storage, &key)? {
+        Some(pending) if !pending.activation.is_reached(&env.block) => {
+            remove_pending_dist(&mut deps.storage, &key);
+        }
+        Some(_) => {
+            return Err(StdError::generic_err(
+                "The distribution change has already taken effect",
+            ));
+        }
+        None => {
+            return Err(StdError::generic_err(
+                "There is no pending distribution change",
+            ));
+        }
+    }
+
+    let mut logs = action_logs("cancel_distribution_change", &env);
+    logs.extend(dist_key_logs(&key));
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::CancelDistributionChange {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+/// Sets who besides the admins can register tokens
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `policy` - the new registration policy
+pub fn set_registration_policy<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    policy: RegistrationPolicy,
+) -> StdResult<HandleResponse> {
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+
+    config.registration_policy = policy;
+
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    let logs = action_logs("set_registration_policy", &env);
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::SetRegistrationPolicy {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+/// Sets the number of forwarders received funds can have passed through before reaching this
+/// one
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `max_forward_depth` - the new max forward depth
+pub fn set_max_forward_depth<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    max_forward_depth: u32,
+) -> StdResult<HandleResponse> {
+    if max_forward_depth == 0 {
+        return Err(StdError::generic_err("The max forward depth must be at least 1"));
+    }
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+
+    config.max_forward_depth = max_forward_depth;
+
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    let mut logs = action_logs("set_max_forward_depth", &env);
+    logs.push(log("max_forward_depth", max_forward_depth));
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::SetMaxForwardDepth {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+/// Requires the approval of every recipient whose rate a distribution change lowers.  This
+/// can not be undone
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+pub fn lock_recipients<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+
+    config.locked_recipients = true;
+
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    let logs = action_logs("lock_recipients", &env);
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::LockRecipients {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+/// Approves a distribution change that lowers the sender's rate, and makes the change once
+/// every recipient it lowers has approved
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `proposal_id` - id of the distribution change proposal to approve
+pub fn approve_dist_change<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    proposal_id: u64,
+) -> StdResult<HandleResponse> {
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    let proposal_storage = ReadonlyPrefixedStorage::new(PREFIX_DIST_PROPOSALS, &deps.storage);
+    let mut proposal: StoredDistProposal = may_load(&proposal_storage, &proposal_id.to_be_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("Distribution proposal {} does not exist", proposal_id)))?;
+    if proposal.executed {
+        return Err(StdError::generic_err(
+            "This proposal has already been executed",
+        ));
+    }
+    if !proposal.required.contains(&sender_raw) {
+        return Err(StdError::generic_err(
+            "Only recipients whose rate is lowered can approve this change",
+        ));
+    }
+    if proposal.approvals.contains(&sender_raw) {
+        return Err(StdError::generic_err(
+            "You have already approved this proposal",
+        ));
+    }
+    proposal.approvals.push(sender_raw);
+
+    let awaiting = proposal
+        .required
+        .iter()
+        .filter(|r| !proposal.approvals.contains(*r))
+        .count() as u16;
+    if awaiting == 0 {
+        // the approvals only cover lowering the distribution the proposal was made against
+        apply_matured_dist(deps, &env.block, &proposal.key)?;
+        if may_load_dist_info(&deps.storage, &proposal.key)? != proposal.base {
+            return Err(StdError::generic_err(
+                "The distribution has changed since this proposal was made",
+            ));
+        }
+        proposal.executed = true;
+    }
+    let mut proposal_storage = PrefixedStorage::new(PREFIX_DIST_PROPOSALS, &mut deps.storage);
+    save(&mut proposal_storage, &proposal_id.to_be_bytes(), &proposal)?;
+
+    let mut logs = action_logs("approve_distribution_change", &env);
+    logs.push(log("proposal_id", proposal_id));
+    logs.push(log("awaiting", awaiting));
+    if proposal.executed {
+        logs.extend(dist_key_logs(&proposal.key));
+        logs.push(log("distribution_hash", dist_hash(&deps.api, proposal.dist_info.as_ref())?));
+        schedule_dist(deps, &env, &proposal.key, proposal.dist_info)?;
+    }
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::DistributionProposal {
+            proposal_id,
+            awaiting,
+        })?),
+    })
+}
+
+
+/// Moves the sender's share of every distribution to a new address, or records the move for
+/// the new address to accept
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `new_addr` - address to receive the sender's share
+/// * `require_acceptance` - true if the new address must accept the move
+pub fn change_recipient_addr<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    new_addr: HumanAddr,
+    require_acceptance: bool,
+) -> StdResult<HandleResponse> {
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    let new_raw = deps.api.canonical_address(&new_addr)?;
+    if sender_raw == new_raw {
+        return Err(StdError::generic_err(
+            "The new address must be different from the current one",
+        ));
+    }
+
+    if require_acceptance {
+        if !is_recipient(&deps.storage, &sender_raw)? {
+            return Err(StdError::generic_err(
+                "You are not a recipient of any distribution",
+            ));
+        }
+        let mut pending_storage = PrefixedStorage::new(PREFIX_PENDING_RECIPIENT, &mut deps.storage);
+        save(&mut pending_storage, sender_raw.as_slice(), &new_raw)?;
+    } else {
+        move_recipient(deps, &env, &sender_raw, &new_raw)?;
+    }
+
+    let mut logs = action_logs("change_my_recipient_address", &env);
+    logs.push(log("new_address", &new_addr));
+    logs.push(log("require_acceptance", require_acceptance));
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::ChangeMyRecipientAddress {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+/// Accepts the share of a recipient that asked to move it to the sender
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `old_addr` - address of the recipient moving its share
+pub fn accept_recipient_addr<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    old_addr: HumanAddr,
+) -> StdResult<HandleResponse> {
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    let old_raw = deps.api.canonical_address(&old_addr)?;
+    let mut pending_storage = PrefixedStorage::new(PREFIX_PENDING_RECIPIENT, &mut deps.storage);
+    if may_load::<CanonicalAddr, _>(&pending_storage, old_raw.as_slice())? != Some(sender_raw.clone()) {
+        return Err(StdError::generic_err(
+            "This recipient has not asked to move its share to you",
+        ));
+    }
+    remove(&mut pending_storage, old_raw.as_slice());
+
+    move_recipient(deps, &env, &old_raw, &sender_raw)?;
+
+    let mut logs = action_logs("accept_recipient_address", &env);
+    logs.push(log("old_address", &old_addr));
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::AcceptRecipientAddress {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+pub fn create_key<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    entropy: String,
+) -> StdResult<HandleResponse> {
+    let prng_seed: Vec<u8> = load(&deps.storage, PRNG_SEED_KEY)?;
+    let key = ViewingKey::new(&env, &prng_seed, entropy.as_bytes());
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+
+    let mut key_storage = PrefixedStorage::new(PREFIX_VIEW_KEY, &mut deps.storage);
+    save(&mut key_storage, sender_raw.as_slice(), &key.to_hashed())?;
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: action_logs("create_viewing_key", &env),
+        data: Some(to_binary(&HandleAnswer::CreateViewingKey { key })?),
+    })
+}
+
+
+pub fn set_key<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    key: String,
+) -> StdResult<HandleResponse> {
+    let vk = ViewingKey(key);
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+
+    let mut key_storage = PrefixedStorage::new(PREFIX_VIEW_KEY, &mut deps.storage);
+    save(&mut key_storage, sender_raw.as_slice(), &vk.to_hashed())?;
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: action_logs("set_viewing_key", &env),
+        data: Some(to_binary(&HandleAnswer::SetViewingKey {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+pub fn revoke_permit<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    permit_name: String,
+) -> StdResult<HandleResponse> {
+    RevokedPermits::revoke_permit(
+        &mut deps.storage,
+        PREFIX_REVOKED_PERMITS,
+        &env.message.sender,
+        &permit_name,
+    );
+
+    let mut logs = action_logs("revoke_permit", &env);
+    logs.push(log("permit_name", &permit_name));
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::RevokePermit {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+
+/// Returns StdResult<()>
+///
+/// verifies the royalty information is valid and if so, stores the royalty info for the token
+/// or as default
 ///
 /// # Arguments
 ///
 /// * `storage` - a mutable reference to the storage for this RoyaltyInfo
 /// * `api` - a reference to the Api used to convert human and canonical addresses
+/// * `contract` - a reference to this contract's address, which can not be a recipient
 /// * `royalty_info` - an optional reference to the RoyaltyInfo to store
 /// * `default` - an optional reference to the default StoredRoyaltyInfo to use if royalty_info is
 ///               not provided
@@ -275,27 +2247,14 @@ pub fn change_admin<S: Storage, A: Api, Q: Querier>(
 fn store_dist_info<S: Storage, A: Api>(
     storage: &mut S,
     api: &A,
+    contract: &CanonicalAddr,
     royalty_info: Option<&RoyaltyInfo>,
     default: Option<&StoredRoyaltyInfo>,
     key: &[u8],
 ) -> StdResult<()> {
     // if RoyaltyInfo is provided, check and save it
     if let Some(royal_inf) = royalty_info {
-        // the allowed message length won't let enough u16 rates to overflow u128
-        let total_rates: u128 = royal_inf.royalties.iter().map(|r| r.rate as u128).sum();
-        let (royalty_den, overflow) =
-            U256::from(10).overflowing_pow(U256::from(royal_inf.decimal_places_in_rates));
-        if overflow {
-            return Err(StdError::generic_err(
-                "The number of decimal places used in the royalty rates is larger than supported",
-            ));
-        }
-        if U256::from(total_rates) != royalty_den {
-            return Err(StdError::generic_err(
-                "The sum of royalty rates must be 100%",
-            ));
-        }
-        let stored = royal_inf.to_stored(api)?;
+        let stored = validate_dist_info(api, contract, royal_inf)?;
         save(storage, key, &stored)
     } else if let Some(def) = default {
         save(storage, key, def)
@@ -307,6 +2266,688 @@ fn store_dist_info<S: Storage, A: Api>(
 
 
 
+/// Returns StdResult<StoredRoyaltyInfo> from verifying the royalty information is valid
+///
+/// # Arguments
+///
+/// * `api` - a reference to the Api used to convert human and canonical addresses
+/// * `contract` - a reference to this contract's address, which can not be a recipient
+/// * `royal_inf` - a reference to the RoyaltyInfo to verify
+fn validate_dist_info<A: Api>(
+    api: &A,
+    contract: &CanonicalAddr,
+    royal_inf: &RoyaltyInfo,
+) -> StdResult<StoredRoyaltyInfo> {
+    // the allowed message length won't let enough u16 rates to overflow u128
+    let total_rates: u128 = royal_inf.royalties.iter().map(|r| r.rate as u128).sum();
+    let (royalty_den, overflow) =
+        U256::from(10).overflowing_pow(U256::from(royal_inf.decimal_places_in_rates));
+    if overflow {
+        return Err(StdError::generic_err(
+            "The number of decimal places used in the royalty rates is larger than supported",
+        ));
+    }
+    if U256::from(total_rates) != royalty_den {
+        return Err(StdError::generic_err(
+            "The sum of royalty rates must be 100%",
+        ));
+    }
+    // the remainder recipient must already be receiving a share
+    if let Some(remainder) = royal_inf.remainder_recipient.as_ref() {
+        if !royal_inf.royalties.iter().any(|r| r.recipient == *remainder) {
+            return Err(StdError::generic_err(
+                "The remainder recipient must be one of the royalty recipients",
+            ));
+        }
+    }
+    if royal_inf.royalties.iter().any(|r| r.msg.is_some() && r.code_hash.is_none()) {
+        return Err(StdError::generic_err(
+            "A royalty msg can only be sent to a contract recipient with a code hash",
+        ));
+    }
+    let stored = royal_inf.to_stored(api)?;
+    // paying itself would forward the funds again until they run out of hops.  Canonical
+    // addresses are compared so every form of the contract's address is caught
+    if stored.royalties.iter().any(|r| r.recipient == *contract) {
+        return Err(StdError::generic_err(
+            "The contract can not be a recipient of its own distribution",
+        ));
+    }
+    Ok(stored)
+}
+
+
+
+/// Returns StdResult<Option<HandleAnswer>> after changing a distribution.  If recipients are
+/// locked and the change lowers any of their rates, it becomes a proposal those recipients
+/// must approve, and its HandleAnswer is returned
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - a reference to the Env of contract's environment
+/// * `key` - a reference to the key of the distribution
+/// * `dist_info` - the new distribution.  None removes a token's own distribution or a named one
+fn update_dist<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    key: &DistKey,
+    dist_info: Option<&RoyaltyInfo>,
+) -> StdResult<Option<HandleAnswer>> {
+    let stored = dist_info.map(|d| validate_dist_info(&deps.api, &deps.api.canonical_address(&env.contract.address)?, d)).transpose()?;
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+
+    if config.locked_recipients {
+        apply_matured_dist(deps, &env.block, key)?;
+        let base = may_load_dist_info(&deps.storage, key)?;
+        let required = match (base.as_ref(), stored.as_ref(), key) {
+            (Some(old), Some(new), _) => old.lowered_recipients(new),
+            // removing a named distribution leaves its recipients nothing
+            (Some(old), None, DistKey::Named(_)) => old.lowered_recipients(&StoredRoyaltyInfo {
+                decimal_places_in_rates: old.decimal_places_in_rates,
+                royalties: vec![],
+                remainder_recipient: None,
+            }),
+            // removing a token's own distribution falls back to the default
+            (Some(old), None, _) => old.lowered_recipients(&load(&deps.storage, FUNDS_DISTRIBUTION_KEY)?),
+            (None, _, _) => vec![],
+        };
+        if !required.is_empty() {
+            let proposal_id: u64 = may_load(&deps.storage, DIST_PROPOSAL_COUNT_KEY)?.unwrap_or(0);
+            save(&mut deps.storage, DIST_PROPOSAL_COUNT_KEY, &(proposal_id + 1))?;
+            let awaiting = required.len() as u16;
+            let proposal = StoredDistProposal {
+                key: key.clone(),
+                dist_info: stored,
+                base,
+                required,
+                approvals: vec![],
+                executed: false,
+                created_height: env.block.height,
+            };
+            let mut proposal_storage = PrefixedStorage::new(PREFIX_DIST_PROPOSALS, &mut deps.storage);
+            save(&mut proposal_storage, &proposal_id.to_be_bytes(), &proposal)?;
+            return Ok(Some(HandleAnswer::DistributionProposal {
+                proposal_id,
+                awaiting,
+            }));
+        }
+    }
+
+    schedule_dist(deps, env, key, stored)?;
+    Ok(None)
+}
+
+
+
+/// Returns StdResult<()> after changing a distribution immediately if there is no timelock, or
+/// scheduling the change if there is
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - a reference to the Env of contract's environment
+/// * `key` - a reference to the key of the distribution
+/// * `stored` - the verified new distribution.  None removes a token's own distribution or a
+///              named one
+fn schedule_dist<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    key: &DistKey,
+    stored: Option<StoredRoyaltyInfo>,
+) -> StdResult<()> {
+    // a matured change must not be overwritten before it is applied
+    apply_matured_dist(deps, &env.block, key)?;
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    if apply_matured_timelock(&mut config, &env.block) {
+        save(&mut deps.storage, CONFIG_KEY, &config)?;
+    }
+
+    match config.timelock {
+        None => write_dist(deps, key, stored.as_ref()),
+        Some(timelock) => {
+            let pending = StoredPendingDistribution {
+                dist_info: stored,
+                activation: timelock.activation(&env.block),
+            };
+            save_pending_dist(&mut deps.storage, key, &pending)
+        }
+    }
+}
+
+
+
+/// Returns bool, true if a scheduled timelock change took effect
+///
+/// # Arguments
+///
+/// * `config` - a mutable reference to the Config
+/// * `block` - a reference to the current BlockInfo
+fn apply_matured_timelock(config: &mut Config, block: &BlockInfo) -> bool {
+    match config.pending_timelock.take() {
+        Some(pending) if pending.activation.is_reached(block) => {
+            config.timelock = pending.timelock;
+            true
+        }
+        pending => {
+            config.pending_timelock = pending;
+            false
+        }
+    }
+}
+
+
+
+/// Returns StdResult<()> after applying a pending distribution change whose timelock has passed
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `block` - a reference to the current BlockInfo
+/// * `key` - a reference to the key of the distribution
+fn apply_matured_dist<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    block: &BlockInfo,
+    key: &DistKey,
+) -> StdResult<()> {
+    if let Some(pending) = may_load_pending_dist(&deps.storage, key)? {
+        if pending.activation.is_reached(block) {
+            write_dist(deps, key, pending.dist_info.as_ref())?;
+            remove_pending_dist(&mut deps.storage, key);
+        }
+    }
+    Ok(())
+}
+
+
+
+/// Returns StdResult<()> after storing a distribution
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `key` - a reference to the key of the distribution
+/// * `stored` - an optional reference to the verified StoredRoyaltyInfo to store.  If None,
+///              the distribution is removed
+fn write_dist<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    key: &DistKey,
+    stored: Option<&StoredRoyaltyInfo>,
+) -> StdResult<()> {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let contract = &config.contract_address;
+    match key {
+        DistKey::Token(addr) => {
+            let mut token_dist_storage = PrefixedStorage::new(PREFIX_TOKEN_DISTRIBUTION, &mut deps.storage);
+            store_dist_info(&mut token_dist_storage, &deps.api, contract, None, stored, addr.0.as_bytes())
+        }
+        DistKey::Named(name) => {
+            let mut names: Vec<String> = may_load(&deps.storage, DISTRIBUTION_NAMES_KEY)?.unwrap_or_default();
+            let listed = names.contains(name);
+            if stored.is_some() && !listed {
+                names.push(name.clone());
+                save(&mut deps.storage, DISTRIBUTION_NAMES_KEY, &names)?;
+            } else if stored.is_none() && listed {
+                names.retain(|n| n != name);
+                save(&mut deps.storage, DISTRIBUTION_NAMES_KEY, &names)?;
+            }
+            let mut named_dist_storage = PrefixedStorage::new(PREFIX_NAMED_DISTRIBUTION, &mut deps.storage);
+            store_dist_info(&mut named_dist_storage, &deps.api, contract, None, stored, name.as_bytes())
+        }
+        DistKey::Split(split_id) => {
+            let mut split = load_split(&deps.storage, *split_id)?;
+            split.dist_info = stored
+                .cloned()
+                .ok_or_else(|| StdError::generic_err("A split's distribution can not be removed"))?;
+            save_split(&mut deps.storage, *split_id, &split)
+        }
+        DistKey::Default => store_dist_info(&mut deps.storage, &deps.api, contract, None, stored, FUNDS_DISTRIBUTION_KEY),
+    }
+}
+
+
+
+/// Returns StdResult<()> after replacing a recipient's address in the default distribution,
+/// every token and named distribution, every split, and any pending changes to them
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - a reference to the Env of contract's environment
+/// * `old` - a reference to the recipient's current address
+/// * `new` - a reference to the recipient's new address
+fn move_recipient<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    old: &CanonicalAddr,
+    new: &CanonicalAddr,
+) -> StdResult<()> {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    if *new == config.contract_address {
+        return Err(StdError::generic_err(
+            "The contract can not be a recipient of its own distribution",
+        ));
+    }
+    let mut moved = false;
+    for key in dist_keys(&deps.storage)?.iter() {
+        apply_matured_dist(deps, &env.block, key)?;
+        if let Some(mut dist) = may_load_own_dist(&deps.storage, key)? {
+            if dist.replace_recipient(old, new) {
+                write_dist(deps, key, Some(&dist))?;
+                moved = true;
+            }
+        }
+        if let Some(mut pending) = may_load_pending_dist(&deps.storage, key)? {
+            if let Some(dist) = pending.dist_info.as_mut() {
+                if dist.replace_recipient(old, new) {
+                    save_pending_dist(&mut deps.storage, key, &pending)?;
+                    moved = true;
+                }
+            }
+        }
+    }
+    if !moved {
+        return Err(StdError::generic_err(
+            "You are not a recipient of any distribution",
+        ));
+    }
+    Ok(())
+}
+
+
+
+/// Returns StdResult<bool>, true if the address receives a share of any distribution, or of a
+/// pending change to one
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `address` - a reference to the address to look for
+fn is_recipient<S: ReadonlyStorage>(storage: &S, address: &CanonicalAddr) -> StdResult<bool> {
+    for key in dist_keys(storage)?.iter() {
+        let pending = may_load_pending_dist(storage, key)?.and_then(|p| p.dist_info);
+        let found = may_load_own_dist(storage, key)?
+            .into_iter()
+            .chain(pending)
+            .any(|d| d.rate_of(address) > 0);
+        if found {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+
+
+/// Returns StdResult<Vec<DistKey>> of the default distribution, followed by every token ever
+/// registered, which may have its own distribution, every named distribution and every split
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+fn dist_keys<S: ReadonlyStorage>(storage: &S) -> StdResult<Vec<DistKey>> {
+    let registered: Vec<HumanAddr> = may_load(storage, REGISTERED_TOKENS_KEY)?.unwrap_or_default();
+    let names: Vec<String> = may_load(storage, DISTRIBUTION_NAMES_KEY)?.unwrap_or_default();
+    let mut keys = vec![DistKey::Default];
+    keys.extend(registered.into_iter().map(DistKey::Token));
+    keys.extend(names.into_iter().map(DistKey::Named));
+    let split_count: u64 = may_load(storage, SPLIT_COUNT_KEY)?.unwrap_or(0);
+    keys.extend((0..split_count).map(DistKey::Split));
+    Ok(keys)
+}
+
+
+
+/// Returns StdResult<TokenInfo> from querying a snip20 contract's token_info, or an error if
+/// the address and code hash do not belong to a snip20 contract
+///
+/// # Arguments
+///
+/// * `querier` - a reference to the Querier used to query the token
+/// * `token` - a reference to the address of the snip20 contract
+/// * `code_hash` - a reference to the code hash of the snip20 contract
+fn fetch_token_info<Q: Querier>(querier: &Q, token: &HumanAddr, code_hash: &str) -> StdResult<TokenInfo> {
+    token_info_query(querier, BLOCK_SIZE, code_hash.to_string(), token.clone()).map_err(|_| {
+        StdError::generic_err(format!(
+            "{} with the given code hash did not answer a SNIP-20 token_info query",
+            token
+        ))
+    })
+}
+
+
+
+/// Returns StdResult<CosmosMsg> of the message setting a new viewing key for the contract on a
+/// token, after saving the key so the contract can query its balance
+///
+/// # Arguments
+///
+/// * `storage` - a mutable reference to the contract's storage
+/// * `env` - a reference to the Env of contract's environment
+/// * `token` - a reference to the address of the snip20 contract
+/// * `code_hash` - a reference to the code hash of the snip20 contract
+fn token_key_msg<S: Storage>(
+    storage: &mut S,
+    env: &Env,
+    token: &HumanAddr,
+    code_hash: &str,
+) -> StdResult<CosmosMsg> {
+    let prng_seed: Vec<u8> = load(storage, PRNG_SEED_KEY)?;
+    let key = ViewingKey::new(env, &prng_seed, token.0.as_bytes()).to_string();
+    let mut key_storage = PrefixedStorage::new(PREFIX_TOKEN_VIEWING_KEY, storage);
+    save(&mut key_storage, token.0.as_bytes(), &key)?;
+
+    set_viewing_key_msg(key, None, BLOCK_SIZE, code_hash.to_string(), token.clone())
+}
+
+
+
+/// Returns StdResult<u128> of the contract's balance of a token, queried with the viewing key
+/// the contract set on it
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `contract_addr` - a reference to this contract's address
+/// * `token` - a reference to the address of the snip20 contract
+/// * `token_info` - a reference to the token's StoredTokenInfo
+fn contract_balance<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    contract_addr: &HumanAddr,
+    token: &HumanAddr,
+    token_info: &StoredTokenInfo,
+) -> StdResult<u128> {
+    let key_storage = ReadonlyPrefixedStorage::new(PREFIX_TOKEN_VIEWING_KEY, &deps.storage);
+    let key: String = may_load(&key_storage, token.0.as_bytes())?.ok_or_else(|| {
+        StdError::generic_err(format!("The contract has no viewing key for {}", token))
+    })?;
+    let balance = balance_query(
+        &deps.querier,
+        contract_addr.clone(),
+        key,
+        BLOCK_SIZE,
+        token_info.code_hash.clone(),
+        token.clone(),
+    )?;
+    Ok(balance.amount.u128())
+}
+
+
+
+/// Returns StdResult<Option<StoredTokenInfo>> of a token, whether or not it is still registered
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `token` - a reference to the address of the snip20 contract
+fn may_load_token_info<S: ReadonlyStorage>(
+    storage: &S,
+    token: &HumanAddr,
+) -> StdResult<Option<StoredTokenInfo>> {
+    let snip_contract_storage = ReadonlyPrefixedStorage::new(PREFIX_TOKEN_CONTRACT_INFO, storage);
+    may_load(&snip_contract_storage, token.0.as_bytes())
+}
+
+
+
+/// Returns StdResult<StoredTokenInfo> of a token, or an error if it is not registered
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `token` - a reference to the address of the snip20 contract
+fn load_registered_token<S: ReadonlyStorage>(
+    storage: &S,
+    token: &HumanAddr,
+) -> StdResult<StoredTokenInfo> {
+    may_load_token_info(storage, token)?
+        .filter(|info| info.registered)
+        .ok_or_else(|| {
+            StdError::generic_err(
+                "This token is not registered with this contract. Please register it",
+            )
+        })
+}
+
+
+
+/// Returns StdResult<Option<StoredRoyaltyInfo>> of a distribution as stored, without falling
+/// back to the default distribution
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `key` - a reference to the key of the distribution
+fn may_load_own_dist<S: ReadonlyStorage>(
+    storage: &S,
+    key: &DistKey,
+) -> StdResult<Option<StoredRoyaltyInfo>> {
+    match key {
+        DistKey::Token(addr) => {
+            let token_dist_storage = ReadonlyPrefixedStorage::new(PREFIX_TOKEN_DISTRIBUTION, storage);
+            may_load(&token_dist_storage, addr.0.as_bytes())
+        }
+        DistKey::Named(name) => {
+            let named_dist_storage = ReadonlyPrefixedStorage::new(PREFIX_NAMED_DISTRIBUTION, storage);
+            may_load(&named_dist_storage, name.as_bytes())
+        }
+        DistKey::Split(split_id) => {
+            let split_storage = ReadonlyPrefixedStorage::new(PREFIX_SPLITS, storage);
+            Ok(may_load::<StoredSplit, _>(&split_storage, &split_id.to_be_bytes())?.map(|s| s.dist_info))
+        }
+        DistKey::Default => may_load(storage, FUNDS_DISTRIBUTION_KEY),
+    }
+}
+
+
+
+/// Returns StdResult<Option<StoredPendingDistribution>> of the pending change to a distribution
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `key` - a reference to the key of the distribution
+fn may_load_pending_dist<S: ReadonlyStorage>(
+    storage: &S,
+    key: &DistKey,
+) -> StdResult<Option<StoredPendingDistribution>> {
+    match key {
+        DistKey::Token(addr) => {
+            let pending_storage = ReadonlyPrefixedStorage::new(PREFIX_PENDING_TOKEN_DISTRIBUTION, storage);
+            may_load(&pending_storage, addr.0.as_bytes())
+        }
+        DistKey::Named(name) => {
+            let pending_storage = ReadonlyPrefixedStorage::new(PREFIX_PENDING_NAMED_DISTRIBUTION, storage);
+            may_load(&pending_storage, name.as_bytes())
+        }
+        DistKey::Split(split_id) => {
+            let pending_storage = ReadonlyPrefixedStorage::new(PREFIX_PENDING_SPLIT_DISTRIBUTION, storage);
+            may_load(&pending_storage, &split_id.to_be_bytes())
+        }
+        DistKey::Default => may_load(storage, PENDING_DISTRIBUTION_KEY),
+    }
+}
+
+
+
+/// Returns StdResult<()> after saving the pending change to a distribution
+///
+/// # Arguments
+///
+/// * `storage` - a mutable reference to the contract's storage
+/// * `key` - a reference to the key of the distribution
+/// * `pending` - a reference to the pending change
+fn save_pending_dist<S: Storage>(
+    storage: &mut S,
+    key: &DistKey,
+    pending: &StoredPendingDistribution,
+) -> StdResult<()> {
+    match key {
+        DistKey::Token(addr) => {
+            let mut pending_storage = PrefixedStorage::new(PREFIX_PENDING_TOKEN_DISTRIBUTION, storage);
+            save(&mut pending_storage, addr.0.as_bytes(), pending)
+        }
+        DistKey::Named(name) => {
+            let mut pending_storage = PrefixedStorage::new(PREFIX_PENDING_NAMED_DISTRIBUTION, storage);
+            save(&mut pending_storage, name.as_bytes(), pending)
+        }
+        DistKey::Split(split_id) => {
+            let mut pending_storage = PrefixedStorage::new(PREFIX_PENDING_SPLIT_DISTRIBUTION, storage);
+            save(&mut pending_storage, &split_id.to_be_bytes(), pending)
+        }
+        DistKey::Default => save(storage, PENDING_DISTRIBUTION_KEY, pending),
+    }
+}
+
+
+
+/// Removes the pending change to a distribution
+///
+/// # Arguments
+///
+/// * `storage` - a mutable reference to the contract's storage
+/// * `key` - a reference to the key of the distribution
+fn remove_pending_dist<S: Storage>(storage: &mut S, key: &DistKey) {
+    match key {
+        DistKey::Token(addr) => {
+            let mut pending_storage = PrefixedStorage::new(PREFIX_PENDING_TOKEN_DISTRIBUTION, storage);
+            remove(&mut pending_storage, addr.0.as_bytes());
+        }
+        DistKey::Named(name) => {
+            let mut pending_storage = PrefixedStorage::new(PREFIX_PENDING_NAMED_DISTRIBUTION, storage);
+            remove(&mut pending_storage, name.as_bytes());
+        }
+        DistKey::Split(split_id) => {
+            let mut pending_storage = PrefixedStorage::new(PREFIX_PENDING_SPLIT_DISTRIBUTION, storage);
+            remove(&mut pending_storage, &split_id.to_be_bytes());
+        }
+        DistKey::Default => remove(storage, PENDING_DISTRIBUTION_KEY),
+    }
+}
+
+
+
+/// Returns StdResult<(Option<StoredRoyaltyInfo>, Option<StoredPendingDistribution>)> of the
+/// distribution used for a key and the pending change that affects it.  A change whose timelock
+/// has passed by the given height or time is shown in use, as the next forward will apply it.
+/// A token without its own distribution uses, and is affected by changes to, the default
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `key` - a reference to the key of the distribution
+/// * `height` - optional current block height
+/// * `time` - optional current block time in seconds since epoch
+fn effective_dist<S: ReadonlyStorage>(
+    storage: &S,
+    key: &DistKey,
+    height: Option<u64>,
+    time: Option<u64>,
+) -> StdResult<(Option<StoredRoyaltyInfo>, Option<StoredPendingDistribution>)> {
+    let matured = |key: &DistKey| -> StdResult<(Option<StoredRoyaltyInfo>, Option<StoredPendingDistribution>)> {
+        let own = may_load_own_dist(storage, key)?;
+        match may_load_pending_dist(storage, key)? {
+            Some(pending) if pending.activation.is_reached_at(height, time) => Ok((pending.dist_info, None)),
+            pending => Ok((own, pending)),
+        }
+    };
+    match key {
+        DistKey::Token(_) => {
+            let (own, own_pending) = matured(key)?;
+            // a token with its own distribution is unaffected by default changes
+            if own.is_some() {
+                return Ok((own, own_pending));
+            }
+            let (default, default_pending) = matured(&DistKey::Default)?;
+            Ok((default, own_pending.or(default_pending)))
+        }
+        _ => matured(key),
+    }
+}
+
+
+
+/// Returns StdResult<StoredSplit> of a split, or an error if it does not exist
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `split_id` - id of the split
+fn load_split<S: ReadonlyStorage>(storage: &S, split_id: u64) -> StdResult<StoredSplit> {
+    let split_storage = ReadonlyPrefixedStorage::new(PREFIX_SPLITS, storage);
+    may_load(&split_storage, &split_id.to_be_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("Split {} does not exist", split_id)))
+}
+
+/// Returns StdResult<StoredSplit> of a split, or an error if the sender is not its admin
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `env` - a reference to the Env of contract's environment
+/// * `split_id` - id of the split
+fn load_split_as_admin<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    env: &Env,
+    split_id: u64,
+) -> StdResult<StoredSplit> {
+    let split = load_split(&deps.storage, split_id)?;
+    if split.admin != deps.api.canonical_address(&env.message.sender)? {
+        return Err(StdError::generic_err(
+            "This function is only usable by the Admin of the split",
+        ));
+    }
+    Ok(split)
+}
+
+/// Returns StdResult<()> after saving a split
+///
+/// # Arguments
+///
+/// * `storage` - a mutable reference to the contract's storage
+/// * `split_id` - id of the split
+/// * `split` - a reference to the split to save
+fn save_split<S: Storage>(storage: &mut S, split_id: u64, split: &StoredSplit) -> StdResult<()> {
+    let mut split_storage = PrefixedStorage::new(PREFIX_SPLITS, storage);
+    save(&mut split_storage, &split_id.to_be_bytes(), split)
+}
+
+
+
+/// Returns StdResult<Option<StoredRoyaltyInfo>> of the distribution used for a key.  A token
+/// without its own distribution uses the default distribution
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `key` - a reference to the key of the distribution
+fn may_load_dist_info<S: ReadonlyStorage>(
+    storage: &S,
+    key: &DistKey,
+) -> StdResult<Option<StoredRoyaltyInfo>> {
+    match key {
+        DistKey::Token(addr) => load_dist_info(storage, addr).map(Some),
+        _ => may_load_own_dist(storage, key),
+    }
+}
+
+/// Returns StdResult<StoredRoyaltyInfo> of the distribution used for a token, which is the
+/// default distribution unless the token has its own
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `token` - address of the snip20 contract
+fn load_dist_info<S: ReadonlyStorage>(storage: &S, token: &HumanAddr) -> StdResult<StoredRoyaltyInfo> {
+    let token_dist_storage = ReadonlyPrefixedStorage::new(PREFIX_TOKEN_DISTRIBUTION, storage);
+    if let Some(dist) = may_load::<StoredRoyaltyInfo, _>(&token_dist_storage, token.0.as_bytes())? {
+        return Ok(dist);
+    }
+    load(storage, FUNDS_DISTRIBUTION_KEY)
+}
+
+
+
 
 
 
@@ -320,20 +2961,921 @@ pub fn query<S: Storage, A: Api, Q: Querier>(
     msg: QueryMsg,
 ) -> StdResult<Binary> {
     match msg {
-        QueryMsg::QueryDist {} => to_binary(&query_distribution(deps)?),
+        QueryMsg::QueryDist { token, distribution, height, time } => {
+            query_distribution(deps, token, distribution, height, time)
+        }
+        QueryMsg::QueryConfig {} => query_config(deps),
+        QueryMsg::QueryRegisteredTokens { page, page_size } => query_registered_tokens(deps, page, page_size),
+        QueryMsg::QuerySplits { page, page_size } => query_splits(deps, page, page_size),
+        QueryMsg::QuerySplit { split_id } => query_split(deps, split_id),
+        QueryMsg::WithPermit { permit, query } => permit_queries(deps, permit, query),
+        _ => viewing_keys_queries(deps, msg),
+    }
+}
+
+
+
+/// Returns QueryResult of a query authenticated with a permit
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `permit` - the permit signed by the querier
+/// * `query` - the query to perform
+fn permit_queries<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    permit: Permit,
+    query: QueryWithPermit,
+) -> QueryResult {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let contract_address = deps.api.human_address(&config.contract_address)?;
+    let account = HumanAddr(validate(deps, PREFIX_REVOKED_PERMITS, &permit, contract_address)?);
+
+    permit_query_as(deps, &permit, &account, query)
+}
+
+/// Returns QueryResult of a permit query performed for the account that signed the permit
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `permit` - a reference to the permit whose signature has already been validated
+/// * `account` - a reference to the address that signed the permit
+/// * `query` - the query to perform
+pub fn permit_query_as<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    permit: &Permit,
+    account: &HumanAddr,
+    query: QueryWithPermit,
+) -> QueryResult {
+    let required = match query {
+        QueryWithPermit::Claimable { .. }
+        | QueryWithPermit::Share { .. }
+        | QueryWithPermit::PrivateDist { .. }
+        | QueryWithPermit::DistributionProposal { .. }
+        | QueryWithPermit::Earnings {} => TokenPermissions::Balance,
+        QueryWithPermit::Payouts { .. } => TokenPermissions::History,
+        QueryWithPermit::ForwardHistory { .. }
+        | QueryWithPermit::SplitForwards { .. } => TokenPermissions::Owner,
+        QueryWithPermit::Stats { .. } => TokenPermissions::Owner,
+        QueryWithPermit::Proposals { .. }
+        | QueryWithPermit::Proposal { .. }
+        | QueryWithPermit::ContractBalances {} => TokenPermissions::Owner,
+    };
+    if !permit.check_permission(&required) {
+        return Err(StdError::generic_err(format!(
+            "This permit does not have the {:?} permission, got permissions {:?}",
+            required, permit.params.permissions
+        )));
+    }
+
+    match query {
+        QueryWithPermit::Claimable { tokens } => query_claimable(deps, account, tokens),
+        QueryWithPermit::Share { token, distribution, height, time } => {
+            query_share(deps, account, token, distribution, height, time)
+        }
+        QueryWithPermit::PrivateDist { token, distribution, height, time } => {
+            query_private_distribution(deps, account, token, distribution, height, time)
+        }
+        QueryWithPermit::Earnings {} => query_earnings(deps, account),
+        QueryWithPermit::Payouts { token, page, page_size } => query_payouts(deps, account, token, page, page_size),
+        QueryWithPermit::ForwardHistory { token, page, page_size } => query_forward_history(deps, account, token, page, page_size),
+        QueryWithPermit::SplitForwards { split_id, page, page_size } => query_split_forwards(deps, account, split_id, page, page_size),
+        QueryWithPermit::Stats { token, epochs } => query_stats(deps, account, token, epochs),
+        QueryWithPermit::Proposals { page, page_size } => query_proposals(deps, account, page, page_size),
+        QueryWithPermit::Proposal { proposal_id } => query_proposal(deps, account, proposal_id),
+        QueryWithPermit::DistributionProposal { proposal_id } => query_dist_proposal(deps, account, proposal_id),
+        QueryWithPermit::ContractBalances {} => query_contract_balances(deps, account),
+    }
+}
+
+
+
+/// Returns QueryResult of a query authenticated with a viewing key
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `msg` - the query to perform
+fn viewing_keys_queries<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    msg: QueryMsg,
+) -> QueryResult {
+    let (address, key) = msg.get_validation_params()?;
+    let address_raw = deps.api.canonical_address(address)?;
+    check_key(&deps.storage, &address_raw, key)?;
+
+    match msg {
+        QueryMsg::QueryClaimable { address, tokens, .. } => query_claimable(deps, &address, tokens),
+        QueryMsg::QueryShare { address, token, distribution, height, time, .. } => {
+            query_share(deps, &address, token, distribution, height, time)
+        }
+        QueryMsg::QueryPrivateDist { address, token, distribution, height, time, .. } => {
+            query_private_distribution(deps, &address, token, distribution, height, time)
+        }
+        QueryMsg::QueryEarnings { address, .. } => query_earnings(deps, &address),
+        QueryMsg::QueryPayouts { address, token, page, page_size, .. } => query_payouts(deps, &address, token, page, page_size),
+        QueryMsg::QueryForwardHistory { address, token, page, page_size, .. } => query_forward_history(deps, &address, token, page, page_size),
+        QueryMsg::QuerySplitForwards { address, split_id, page, page_size, .. } => query_split_forwards(deps, &address, split_id, page, page_size),
+        QueryMsg::QueryStats { address, token, epochs, .. } => query_stats(deps, &address, token, epochs),
+        QueryMsg::QueryProposals { address, page, page_size, .. } => query_proposals(deps, &address, page, page_size),
+        QueryMsg::QueryProposal { address, proposal_id, .. } => query_proposal(deps, &address, proposal_id),
+        QueryMsg::QueryDistributionProposal { address, proposal_id, .. } => query_dist_proposal(deps, &address, proposal_id),
+        QueryMsg::QueryContractBalances { address, .. } => query_contract_balances(deps, &address),
+        _ => Err(StdError::generic_err("This query does not use a viewing key")),
+    }
+}
+
+
+
+/// Returns StdResult<()> if the viewing key matches the one set for the address
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `address` - a reference to the address whose key is checked
+/// * `key` - the viewing key to check
+fn check_key<S: ReadonlyStorage>(storage: &S, address: &CanonicalAddr, key: &str) -> StdResult<()> {
+    let key_storage = ReadonlyPrefixedStorage::new(PREFIX_VIEW_KEY, storage);
+    let expected: Option<[u8; VIEWING_KEY_SIZE]> = may_load(&key_storage, address.as_slice())?;
+    let input = ViewingKey(key.to_string());
+
+    match expected {
+        Some(hashed) if input.check_viewing_key(&hashed) => Ok(()),
+        _ => {
+            // compare against a dummy key so a missing key takes as long as a wrong one
+            input.check_viewing_key(&[0u8; VIEWING_KEY_SIZE]);
+            Err(StdError::generic_err(
+                "Wrong viewing key for this address or viewing key not set",
+            ))
+        }
+    }
+}
+
+
+
+fn query_claimable<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+    tokens: Vec<HumanAddr>,
+) -> QueryResult {
+    let address_raw = deps.api.canonical_address(address)?;
+    let claimable_storage = ReadonlyPrefixedStorage::multilevel(&[PREFIX_CLAIMABLE, address_raw.as_slice()], &deps.storage);
+
+    let balances = tokens
+        .into_iter()
+        .map(|token| {
+            let amount: u128 = may_load(&claimable_storage, token.0.as_bytes())?.unwrap_or(0);
+            Ok(TokenBalance {
+                token,
+                amount: Uint128(amount),
+            })
+        })
+        .collect::<StdResult<Vec<TokenBalance>>>()?;
+
+    to_binary(&QueryAnswer::Claimable { balances })
+}
+
+
+
+fn query_share<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+    token: Option<HumanAddr>,
+    distribution: Option<String>,
+    height: Option<u64>,
+    time: Option<u64>,
+) -> QueryResult {
+    let address_raw = deps.api.canonical_address(address)?;
+    let key = dist_key(token.clone(), distribution.clone())?;
+    let royalty_list = effective_dist(&deps.storage, &key, height, time)?
+        .0
+        .ok_or_else(|| StdError::generic_err("There is no such distribution"))?;
+    let rate: u128 = royalty_list
+        .royalties
+        .iter()
+        .filter(|r| r.recipient == address_raw)
+        .map(|r| r.rate as u128)
+        .sum();
+
+    to_binary(&QueryAnswer::Share {
+        token,
+        distribution,
+        decimal_places_in_rates: royalty_list.decimal_places_in_rates,
+        rate: Uint128(rate),
+    })
+}
+
+
+
+fn query_earnings<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+) -> QueryResult {
+    let address_raw = deps.api.canonical_address(address)?;
+    let earned_tokens_storage = ReadonlyPrefixedStorage::new(PREFIX_EARNED_TOKENS, &deps.storage);
+    let earned_tokens: Vec<String> =
+        may_load(&earned_tokens_storage, address_raw.as_slice())?.unwrap_or_default();
+    let earnings_storage = ReadonlyPrefixedStorage::multilevel(&[PREFIX_EARNINGS, address_raw.as_slice()], &deps.storage);
+
+    let earnings = earned_tokens
+        .into_iter()
+        .map(|token| {
+            let amount: u128 = may_load(&earnings_storage, token.as_bytes())?.unwrap_or(0);
+            Ok(TokenTotal {
+                token,
+                amount: Uint128(amount),
+            })
+        })
+        .collect::<StdResult<Vec<TokenTotal>>>()?;
+
+    to_binary(&QueryAnswer::Earnings { earnings })
+}
+
+
+
+fn query_payouts<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+    token: Option<String>,
+    page: Option<u32>,
+    page_size: u32,
+) -> QueryResult {
+    let address_raw = deps.api.canonical_address(address)?;
+    let history_storage = ReadonlyPrefixedStorage::multilevel(&[PREFIX_PAYOUT_HISTORY, address_raw.as_slice()], &deps.storage);
+    let skip = page.unwrap_or(0).saturating_mul(page_size);
+
+    let history = match AppendStore::<StoredPayoutRecord, _>::attach(&history_storage) {
+        Some(history) => history?,
+        None => return to_binary(&QueryAnswer::Payouts { payouts: vec![], total: 0 }),
+    };
+    let positions = match token {
+        Some(token) => {
+            let token_history_storage = ReadonlyPrefixedStorage::multilevel(
+                &[PREFIX_TOKEN_PAYOUT_HISTORY, address_raw.as_slice(), token.as_bytes()],
+                &deps.storage,
+            );
+            match AppendStore::<u32, _>::attach(&token_history_storage) {
+                Some(token_history) => token_history?.iter().collect::<StdResult<Vec<u32>>>()?,
+                None => vec![],
+            }
+        }
+        None => (0..history.len()).collect(),
+    };
+    let payouts = positions
+        .iter()
+        .rev()
+        .skip(skip as usize)
+        .take(page_size as usize)
+        .map(|p| history.get_at(*p).map(PayoutRecord::from))
+        .collect::<StdResult<Vec<PayoutRecord>>>()?;
+
+    to_binary(&QueryAnswer::Payouts {
+        payouts,
+        total: positions.len() as u32,
+    })
+}
+
+
+
+/// Returns QueryResult displaying a page of the ledger of forwards, most recent first
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `address` - a reference to the address performing the query
+/// * `token` - optional snip20 contract address or native denom to only display forwards of
+/// * `page` - optional page to display, starting at 0
+/// * `page_size` - number of forwards per page
+fn query_forward_history<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+    token: Option<String>,
+    page: Option<u32>,
+    page_size: u32,
+) -> QueryResult {
+    check_admin_query(deps, address)?;
+    let ledger_storage = ReadonlyPrefixedStorage::new(PREFIX_FORWARD_LEDGER, &deps.storage);
+    let skip = page.unwrap_or(0).saturating_mul(page_size);
+
+    let ledger = match AppendStore::<StoredForward, _>::attach(&ledger_storage) {
+        Some(ledger) => ledger?,
+        None => return to_binary(&QueryAnswer::ForwardHistory { forwards: vec![], total: 0 }),
+    };
+    let ids = match token {
+        Some(token) => {
+            let token_forwards_storage = ReadonlyPrefixedStorage::multilevel(&[PREFIX_TOKEN_FORWARDS, token.as_bytes()], &deps.storage);
+            match AppendStore::<u32, _>::attach(&token_forwards_storage) {
+                Some(token_forwards) => token_forwards?.iter().collect::<StdResult<Vec<u32>>>()?,
+                None => vec![],
+            }
+        }
+        None => (0..ledger.len()).collect(),
+    };
+    let forwards = ids
+        .iter()
+        .rev()
+        .skip(skip as usize)
+        .take(page_size as usize)
+        .map(|id| forward_record(&deps.api, *id, ledger.get_at(*id)?))
+        .collect::<StdResult<Vec<ForwardRecord>>>()?;
+
+    to_binary(&QueryAnswer::ForwardHistory {
+        forwards,
+        total: ids.len() as u32,
+    })
+}
+
+/// Returns QueryResult displaying a page of the ledger of a split's forwards, most recent first
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `address` - a reference to the address performing the query
+/// * `split_id` - id of the split
+/// * `page` - optional page to display, starting at 0
+/// * `page_size` - number of forwards per page
+fn query_split_forwards<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+    split_id: u64,
+    page: Option<u32>,
+    page_size: u32,
+) -> QueryResult {
+    if load_split(&deps.storage, split_id)?.admin != deps.api.canonical_address(address)? {
+        return Err(StdError::generic_err(
+            "This query is only usable by the Admin of the split",
+        ));
+    }
+    let ledger_storage = ReadonlyPrefixedStorage::multilevel(&[PREFIX_SPLIT_LEDGER, &split_id.to_be_bytes()], &deps.storage);
+    let skip = page.unwrap_or(0).saturating_mul(page_size);
+
+    let ledger = match AppendStore::<StoredForward, _>::attach(&ledger_storage) {
+        Some(ledger) => ledger?,
+        None => return to_binary(&QueryAnswer::ForwardHistory { forwards: vec![], total: 0 }),
+    };
+    let forwards = (0..ledger.len())
+        .rev()
+        .skip(skip as usize)
+        .take(page_size as usize)
+        .map(|id| forward_record(&deps.api, id, ledger.get_at(id)?))
+        .collect::<StdResult<Vec<ForwardRecord>>>()?;
+
+    to_binary(&QueryAnswer::ForwardHistory {
+        forwards,
+        total: ledger.len(),
+    })
+}
+
+/// Returns StdResult<ForwardRecord> displaying a forward from a ledger
+///
+/// # Arguments
+///
+/// * `api` - a reference to the Api used to convert human and canonical addresses
+/// * `forward_id` - id of the forward in its ledger
+/// * `forward` - the forward to display
+fn forward_record<A: Api>(api: &A, forward_id: u32, forward: StoredForward) -> StdResult<ForwardRecord> {
+    Ok(ForwardRecord {
+        forward_id,
+        token: forward.token,
+        from: api.human_address(&forward.from)?,
+        amount: Uint128(forward.amount),
+        payouts: forward
+            .payouts
+            .iter()
+            .map(|p| {
+                Ok(Payout {
+                    recipient: api.human_address(&p.recipient)?,
+                    amount: Uint128(p.amount),
+                })
+            })
+            .collect::<StdResult<Vec<Payout>>>()?,
+        dust: Uint128(forward.dust),
+        block_height: forward.block_height,
+        block_time: forward.block_time,
+    })
+}
+
+/// Returns QueryResult displaying the lifetime totals of a token, and its totals in every
+/// epoch overlapping a range of block heights that had forwards
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `address` - a reference to the address performing the query
+/// * `token` - snip20 contract address or native denom
+/// * `epochs` - optional range of block heights to display the epochs of
+fn query_stats<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+    token: String,
+    epochs: Option<HeightRange>,
+) -> QueryResult {
+    check_admin_query(deps, address)?;
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let stats_storage = ReadonlyPrefixedStorage::new(PREFIX_TOKEN_STATS, &deps.storage);
+    let stats: StoredStats = may_load(&stats_storage, token.as_bytes())?.unwrap_or_default();
+    let recipients_storage = ReadonlyPrefixedStorage::new(PREFIX_TOKEN_RECIPIENTS, &deps.storage);
+    let recipients: Vec<CanonicalAddr> =
+        may_load(&recipients_storage, token.as_bytes())?.unwrap_or_default();
+
+    let mut distributed: Vec<Payout> = Vec::new();
+    for recipient in recipients.iter() {
+        let earnings_storage = ReadonlyPrefixedStorage::multilevel(&[PREFIX_EARNINGS, recipient.as_slice()], &deps.storage);
+        let amount: u128 = may_load(&earnings_storage, token.as_bytes())?.unwrap_or(0);
+        distributed.push(Payout {
+            recipient: deps.api.human_address(recipient)?,
+            amount: Uint128(amount),
+        });
+    }
+
+    let mut epoch_list: Vec<EpochStats> = Vec::new();
+    if let Some(range) = epochs {
+        if range.start_height > range.end_height {
+            return Err(StdError::generic_err("The start height can not be after the end height"));
+        }
+        let first = range.start_height / config.epoch_length;
+        let last = range.end_height / config.epoch_length;
+        if last - first >= MAX_QUERY_EPOCHS {
+            return Err(StdError::generic_err(format!(
+                "A stats query can not span more than {} epochs",
+                MAX_QUERY_EPOCHS
+            )));
+        }
+        let epoch_stats_storage = ReadonlyPrefixedStorage::multilevel(&[PREFIX_EPOCH_STATS, token.as_bytes()], &deps.storage);
+        for epoch in first..=last {
+            let epoch_key = epoch.to_be_bytes();
+            let epoch_stats: Option<StoredStats> = may_load(&epoch_stats_storage, &epoch_key)?;
+            if let Some(epoch_stats) = epoch_stats {
+                let paid_storage = ReadonlyPrefixedStorage::multilevel(&[PREFIX_EPOCH_PAID, token.as_bytes(), &epoch_key], &deps.storage);
+                let mut epoch_distributed: Vec<Payout> = Vec::new();
+                for recipient in recipients.iter() {
+                    let amount: Option<u128> = may_load(&paid_storage, recipient.as_slice())?;
+                    if let Some(amount) = amount {
+                        epoch_distributed.push(Payout {
+                            recipient: deps.api.human_address(recipient)?,
+                            amount: Uint128(amount),
+                        });
+                    }
+                }
+                let start_height = epoch * config.epoch_length;
+                epoch_list.push(EpochStats {
+                    epoch,
+                    start_height,
+                    end_height: start_height.saturating_add(config.epoch_length - 1),
+                    received: Uint128(epoch_stats.received),
+                    dust: Uint128(epoch_stats.dust),
+                    forwards: epoch_stats.forwards,
+                    distributed: epoch_distributed,
+                });
+            }
+        }
     }
+
+    // only snip20s are credited in pull mode, so a native denom never has anything unclaimed
+    let unclaimed = total_claimable(&deps.storage, &HumanAddr(token.clone()))?;
+    to_binary(&QueryAnswer::Stats {
+        token,
+        received: Uint128(stats.received),
+        dust: Uint128(stats.dust),
+        forwards: stats.forwards,
+        distributed,
+        unclaimed: Uint128(unclaimed),
+        epoch_length: config.epoch_length,
+        epochs: epoch_list,
+    })
 }
 
 
 
-fn query_distribution<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> QueryResult {
+fn query_distribution<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    token: Option<HumanAddr>,
+    distribution: Option<String>,
+    height: Option<u64>,
+    time: Option<u64>,
+) -> QueryResult {
 
-    let royalty = may_load::<StoredRoyaltyInfo, _>(&deps.storage, FUNDS_DISTRIBUTION_KEY)?;
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let key = dist_key(token, distribution)?;
+    let (royalty, pending) = effective_dist(&deps.storage, &key, height, time)?;
 
 
     to_binary(&QueryAnswer::RoyaltyInfo {
         royalty_info: royalty
-            .map(|s| s.to_human(&deps.api, false))
+            .map(|s| s.to_human(&deps.api, config.hide_recipients))
+            .transpose()?,
+        pending: pending
+            .map(|p| -> StdResult<PendingDistribution> {
+                Ok(PendingDistribution {
+                    royalty_info: p
+                        .dist_info
+                        .map(|s| s.to_human(&deps.api, config.hide_recipients))
+                        .transpose()?,
+                    activation: p.activation,
+                })
+            })
+            .transpose()?,
+    })
+
+}
+
+
+
+/// Returns StdResult<()> if the address is an admin
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `address` - a reference to the address to check
+fn check_admin_query<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+) -> StdResult<()> {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let address_raw = deps.api.canonical_address(address)?;
+    if !config.admins.contains(&address_raw) {
+        return Err(StdError::generic_err(
+            "This query is only usable by the Admin",
+        ));
+    }
+    Ok(())
+}
+
+
+
+/// Returns StdResult<ProposalInfo> from creating a ProposalInfo from a StoredProposal
+///
+/// # Arguments
+///
+/// * `api` - a reference to the Api used to convert human and canonical addresses
+/// * `proposal_id` - id of the proposal
+/// * `proposal` - the stored proposal
+fn proposal_info<A: Api>(api: &A, proposal_id: u64, proposal: StoredProposal) -> StdResult<ProposalInfo> {
+    Ok(ProposalInfo {
+        proposal_id,
+        action: proposal.action,
+        approvals: proposal
+            .approvals
+            .iter()
+            .map(|a| api.human_address(a))
+            .collect::<StdResult<Vec<HumanAddr>>>()?,
+        executed: proposal.executed,
+        created_height: proposal.created_height,
+    })
+}
+
+
+
+/// Returns QueryResult displaying a page of the admin proposals, most recent first.  Pages
+/// are taken by proposal id, so only the proposals on the page are read, and executed
+/// proposals are listed with their `executed` flag set
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `address` - a reference to the address of the admin querying
+/// * `page` - optional page to display
+/// * `page_size` - number of proposals per page
+fn query_proposals<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+    page: Option<u32>,
+    page_size: u32,
+) -> QueryResult {
+    check_admin_query(deps, address)?;
+    let total: u64 = may_load(&deps.storage, PROPOSAL_COUNT_KEY)?.unwrap_or(0);
+    let proposal_storage = ReadonlyPrefixedStorage::new(PREFIX_PROPOSALS, &deps.storage);
+    let skip = (page.unwrap_or(0) as u64).saturating_mul(page_size as u64);
+    let newest = total.saturating_sub(skip);
+    let oldest = newest.saturating_sub(page_size as u64);
+
+    let proposals = (oldest..newest)
+        .rev()
+        .map(|proposal_id| {
+            let proposal: StoredProposal = load(&proposal_storage, &proposal_id.to_be_bytes())?;
+            proposal_info(&deps.api, proposal_id, proposal)
+        })
+        .collect::<StdResult<Vec<ProposalInfo>>>()?;
+
+    to_binary(&QueryAnswer::Proposals { proposals, total })
+}
+
+
+
+fn query_proposal<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+    proposal_id: u64,
+) -> QueryResult {
+    check_admin_query(deps, address)?;
+    let proposal_storage = ReadonlyPrefixedStorage::new(PREFIX_PROPOSALS, &deps.storage);
+    let proposal: StoredProposal = may_load(&proposal_storage, &proposal_id.to_be_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("Proposal {} does not exist", proposal_id)))?;
+
+    to_binary(&QueryAnswer::Proposal {
+        proposal: proposal_info(&deps.api, proposal_id, proposal)?,
+    })
+}
+
+
+
+/// Returns QueryResult displaying a distribution change awaiting recipient approval.  Admins
+/// see every address, and the recipients it lowers see only their own
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `address` - a reference to the address performing the query
+/// * `proposal_id` - id of the distribution change proposal
+fn query_dist_proposal<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+    proposal_id: u64,
+) -> QueryResult {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let address_raw = deps.api.canonical_address(address)?;
+    let proposal_storage = ReadonlyPrefixedStorage::new(PREFIX_DIST_PROPOSALS, &deps.storage);
+    let proposal: StoredDistProposal = may_load(&proposal_storage, &proposal_id.to_be_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("Distribution proposal {} does not exist", proposal_id)))?;
+
+    // a split's admin sees its proposals as the contract's admins do
+    let is_admin = match &proposal.key {
+        DistKey::Split(split_id) => load_split(&deps.storage, *split_id)?.admin == address_raw,
+        _ => false,
+    } || config.admins.contains(&address_raw);
+    if !is_admin && !proposal.required.contains(&address_raw) {
+        return Err(StdError::generic_err(
+            "This query is only usable by the Admin, the split's admin and the recipients whose rates are lowered",
+        ));
+    }
+    let shown = |addrs: &[CanonicalAddr]| {
+        addrs
+            .iter()
+            .filter(|a| is_admin || **a == address_raw)
+            .map(|a| deps.api.human_address(a))
+            .collect::<StdResult<Vec<HumanAddr>>>()
+    };
+
+    to_binary(&QueryAnswer::DistributionProposal {
+        proposal: DistributionProposalInfo {
+            proposal_id,
+            royalty_info: proposal
+                .dist_info
+                .as_ref()
+                .map(|s| {
+                    if is_admin {
+                        s.to_human(&deps.api, false)
+                    } else {
+                        s.to_human_for(&deps.api, &address_raw)
+                    }
+                })
+                .transpose()?,
+            required: shown(&proposal.required)?,
+            approvals: shown(&proposal.approvals)?,
+            token: match &proposal.key {
+                DistKey::Token(addr) => Some(addr.clone()),
+                _ => None,
+            },
+            distribution: match &proposal.key {
+                DistKey::Named(name) => Some(name.clone()),
+                _ => None,
+            },
+            split_id: match proposal.key {
+                DistKey::Split(split_id) => Some(split_id),
+                _ => None,
+            },
+            executed: proposal.executed,
+            created_height: proposal.created_height,
+        },
+    })
+}
+
+
+
+/// Returns QueryResult displaying the contract's balance of every registered token.  A token
+/// that fails to answer, such as one rejecting the contract's viewing key, is listed with its
+/// error instead of failing the whole query
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `address` - a reference to the address performing the query
+fn query_contract_balances<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+) -> QueryResult {
+    check_admin_query(deps, address)?;
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let contract_addr = deps.api.human_address(&config.contract_address)?;
+    let addresses: Vec<HumanAddr> = may_load(&deps.storage, REGISTERED_TOKENS_KEY)?.unwrap_or_default();
+
+    let mut balances: Vec<TokenBalance> = Vec::new();
+    let mut errors: Vec<TokenError> = Vec::new();
+    for token in addresses.into_iter() {
+        if let Ok(token_info) = load_registered_token(&deps.storage, &token) {
+            match contract_balance(deps, &contract_addr, &token, &token_info) {
+                Ok(amount) => balances.push(TokenBalance {
+                    token,
+                    amount: Uint128(amount),
+                }),
+                Err(e) => errors.push(TokenError {
+                    token,
+                    error: e.to_string(),
+                }),
+            }
+        }
+    }
+
+    to_binary(&QueryAnswer::ContractBalances { balances, errors })
+}
+
+
+
+/// Returns QueryResult displaying a page of the registered tokens
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `page` - optional page to display, starting at 0
+/// * `page_size` - number of tokens per page
+fn query_registered_tokens<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    page: Option<u32>,
+    page_size: u32,
+) -> QueryResult {
+    let addresses: Vec<HumanAddr> = may_load(&deps.storage, REGISTERED_TOKENS_KEY)?.unwrap_or_default();
+    let skip = page.unwrap_or(0).saturating_mul(page_size) as usize;
+
+    let mut registered: Vec<RegisteredToken> = Vec::new();
+    for address in addresses.into_iter() {
+        let info = may_load_token_info(&deps.storage, &address)?
+            .ok_or_else(|| StdError::generic_err(format!("Missing info for token {}", address)))?;
+        if info.registered {
+            registered.push(RegisteredToken {
+                address,
+                code_hash: info.code_hash,
+                name: info.name,
+                symbol: info.symbol,
+                decimals: info.decimals,
+                registered_height: info.registered_height,
+            });
+        }
+    }
+    let total = registered.len() as u32;
+    let tokens = registered
+        .into_iter()
+        .skip(skip)
+        .take(page_size as usize)
+        .collect();
+
+    to_binary(&QueryAnswer::RegisteredTokens { tokens, total })
+}
+
+
+
+/// Returns QueryResult displaying the splits, in creation order
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `page` - optional page to display
+/// * `page_size` - number of splits per page
+fn query_splits<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    page: Option<u32>,
+    page_size: u32,
+) -> QueryResult {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let total: u64 = may_load(&deps.storage, SPLIT_COUNT_KEY)?.unwrap_or(0);
+    let start = (page.unwrap_or(0) as u64).saturating_mul(page_size as u64);
+    let end = start.saturating_add(page_size as u64).min(total);
+
+    let splits = (start..end)
+        .map(|split_id| split_info(&deps.storage, &deps.api, config.hide_recipients, split_id, load_split(&deps.storage, split_id)?))
+        .collect::<StdResult<Vec<SplitInfo>>>()?;
+
+    to_binary(&QueryAnswer::Splits { splits, total })
+}
+
+
+
+/// Returns QueryResult displaying a split
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `split_id` - id of the split
+fn query_split<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>, split_id: u64) -> QueryResult {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let split = load_split(&deps.storage, split_id)?;
+
+    to_binary(&QueryAnswer::Split {
+        split: split_info(&deps.storage, &deps.api, config.hide_recipients, split_id, split)?,
+    })
+}
+
+
+
+/// Returns StdResult<SplitInfo> displaying a split and its pending change
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `api` - a reference to the Api used to convert human and canonical addresses
+/// * `hide_addr` - true if recipient addresses should be kept hidden
+/// * `split_id` - id of the split
+/// * `split` - the split to display
+fn split_info<S: ReadonlyStorage, A: Api>(
+    storage: &S,
+    api: &A,
+    hide_addr: bool,
+    split_id: u64,
+    split: StoredSplit,
+) -> StdResult<SplitInfo> {
+    let pending = may_load_pending_dist(storage, &DistKey::Split(split_id))?;
+    Ok(SplitInfo {
+        split_id,
+        name: split.name,
+        admin: api.human_address(&split.admin)?,
+        pending_admin: split.pending_admin.map(|a| api.human_address(&a)).transpose()?,
+        royalty_info: split.dist_info.to_human(api, hide_addr)?,
+        pending: pending
+            .map(|p| -> StdResult<PendingDistribution> {
+                Ok(PendingDistribution {
+                    royalty_info: p.dist_info.map(|s| s.to_human(api, hide_addr)).transpose()?,
+                    activation: p.activation,
+                })
+            })
+            .transpose()?,
+        created_height: split.created_height,
+    })
+}
+
+
+
+fn query_config<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> QueryResult {
+
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+
+
+    to_binary(&QueryAnswer::Config {
+        admins: config
+            .admins
+            .iter()
+            .map(|a| deps.api.human_address(a))
+            .collect::<StdResult<Vec<HumanAddr>>>()?,
+        threshold: config.threshold,
+        pending_admin: config
+            .pending_admin
+            .map(|a| deps.api.human_address(&a))
+            .transpose()?,
+        distribution_mode: config.distribution_mode,
+        hide_recipients: config.hide_recipients,
+        timelock: config.timelock,
+        pending_timelock: config.pending_timelock,
+        locked_recipients: config.locked_recipients,
+        registration_policy: config.registration_policy,
+        stats_epoch_length: config.epoch_length,
+        distributions: may_load(&deps.storage, DISTRIBUTION_NAMES_KEY)?.unwrap_or_default(),
+        max_forward_depth: config.max_forward_depth,
+    })
+
+}
+
+
+
+fn query_private_distribution<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+    token: Option<HumanAddr>,
+    distribution: Option<String>,
+    height: Option<u64>,
+    time: Option<u64>,
+) -> QueryResult {
+
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let address_raw = deps.api.canonical_address(address)?;
+    let key = dist_key(token, distribution)?;
+    let (royalty, pending) = effective_dist(&deps.storage, &key, height, time)?;
+    let display = |s: StoredRoyaltyInfo| {
+        if config.admins.contains(&address_raw) {
+            s.to_human(&deps.api, false)
+        } else {
+            s.to_human_for(&deps.api, &address_raw)
+        }
+    };
+
+
+    to_binary(&QueryAnswer::RoyaltyInfo {
+        royalty_info: royalty.map(display).transpose()?,
+        pending: pending
+            .map(|p| -> StdResult<PendingDistribution> {
+                Ok(PendingDistribution {
+                    royalty_info: p.dist_info.map(display).transpose()?,
+                    activation: p.activation,
+                })
+            })
             .transpose()?,
     })
 
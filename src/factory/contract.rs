@@ -0,0 +1,305 @@
+use cosmwasm_std::{
+    log, to_binary, Api, CanonicalAddr, CosmosMsg, Env, Extern, HandleResponse, HumanAddr, InitResponse, Querier,
+    QueryResult, StdError, StdResult, Storage, WasmMsg,
+};
+use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
+use secret_toolkit::storage::{AppendStore, AppendStoreMut};
+use secret_toolkit::utils::pad_handle_result;
+
+use crate::contract::BLOCK_SIZE;
+use crate::factory::msg::{ForwarderInfo, HandleAnswer, HandleMsg, InitMsg, QueryAnswer, QueryMsg};
+use crate::factory::state::{Config, StoredForwarder, StoredPendingForwarder, CONFIG_KEY, FORWARDER_COUNT_KEY,
+    PREFIX_INITIAL_ADMIN_FORWARDERS, PREFIX_CREATOR_FORWARDERS, PREFIX_FORWARDERS, PREFIX_PENDING_FORWARDERS, PRNG_SEED_KEY};
+use crate::msg::{Callback, InitMsg as ForwarderInitMsg, ResponseStatus};
+use crate::state::{save, load, may_load, remove};
+use crate::viewing_key::{sha_256, ViewingKey};
+
+
+pub fn init<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    msg: InitMsg,
+) -> StdResult<InitResponse> {
+    let admin = msg.admin.unwrap_or(env.message.sender);
+    let config = Config {
+        admin: deps.api.canonical_address(&admin)?,
+        forwarder_code_id: msg.forwarder_code_id,
+        forwarder_code_hash: msg.forwarder_code_hash,
+    };
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+    let prng_seed: Vec<u8> = sha_256(msg.entropy.as_bytes()).to_vec();
+    save(&mut deps.storage, PRNG_SEED_KEY, &prng_seed)?;
+
+    Ok(InitResponse::default())
+}
+
+pub fn handle<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    msg: HandleMsg,
+) -> StdResult<HandleResponse> {
+    let response = match msg {
+        HandleMsg::CreateForwarder { label, forwarder } => create_forwarder(deps, env, label, forwarder),
+        HandleMsg::RegisterForwarder { forwarder_id, key } => register_forwarder(deps, env, forwarder_id, key),
+        HandleMsg::SetForwarderCode { code_id, code_hash } => set_forwarder_code(deps, env, code_id, code_hash),
+    };
+    pad_handle_result(response, BLOCK_SIZE)
+}
+
+
+/// Instantiates a fund-forwarding contract that calls back to record its address
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `label` - label of the new contract
+/// * `forwarder` - init msg of the new contract
+pub fn create_forwarder<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    label: String,
+    mut forwarder: ForwarderInitMsg,
+) -> StdResult<HandleResponse> {
+    if forwarder.callback.is_some() {
+        return Err(StdError::generic_err(
+            "The factory sets the callback of the forwarders it creates",
+        ));
+    }
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let forwarder_id: u64 = may_load(&deps.storage, FORWARDER_COUNT_KEY)?.unwrap_or(0);
+    save(&mut deps.storage, FORWARDER_COUNT_KEY, &(forwarder_id + 1))?;
+
+    let prng_seed: Vec<u8> = load(&deps.storage, PRNG_SEED_KEY)?;
+    let key = ViewingKey::new(&env, &prng_seed, &forwarder_id.to_be_bytes());
+    let admins = std::iter::once(&forwarder.admin)
+        .chain(forwarder.co_admins.iter())
+        .map(|a| deps.api.canonical_address(a))
+        .collect::<StdResult<Vec<CanonicalAddr>>>()?;
+    let pending = StoredPendingForwarder {
+        label: label.clone(),
+        creator: deps.api.canonical_address(&env.message.sender)?,
+        admins,
+        key_hash: key.to_hashed(),
+    };
+    let mut pending_storage = PrefixedStorage::new(PREFIX_PENDING_FORWARDERS, &mut deps.storage);
+    save(&mut pending_storage, &forwarder_id.to_be_bytes(), &pending)?;
+
+    forwarder.callback = Some(Callback {
+        msg: to_binary(&HandleMsg::RegisterForwarder {
+            forwarder_id,
+            key: key.to_string(),
+        })?,
+        contract_addr: env.contract.address.clone(),
+        code_hash: env.contract_code_hash.clone(),
+    });
+    let instantiate = CosmosMsg::Wasm(WasmMsg::Instantiate {
+        code_id: config.forwarder_code_id,
+        callback_code_hash: config.forwarder_code_hash,
+        msg: to_binary(&forwarder)?,
+        send: env.message.sent_funds.clone(),
+        label: label.clone(),
+    });
+
+
+    Ok(HandleResponse {
+        messages: vec![instantiate],
+        log: vec![
+            log("action", "create_forwarder"),
+            log("sender", &env.message.sender),
+            log("forwarder_id", forwarder_id),
+            log("label", &label),
+        ],
+        data: Some(to_binary(&HandleAnswer::CreateForwarder { forwarder_id })?),
+    })
+}
+
+
+/// Records the address of a forwarder when its instantiation calls back
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `forwarder_id` - id of the forwarder
+/// * `key` - key the factory gave the forwarder to call back with
+pub fn register_forwarder<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    forwarder_id: u64,
+    key: String,
+) -> StdResult<HandleResponse> {
+    let id_key = forwarder_id.to_be_bytes();
+    let pending_storage = ReadonlyPrefixedStorage::new(PREFIX_PENDING_FORWARDERS, &deps.storage);
+    let pending: StoredPendingForwarder = may_load(&pending_storage, &id_key)?
+        .filter(|p: &StoredPendingForwarder| ViewingKey(key).check_viewing_key(&p.key_hash))
+        .ok_or_else(|| StdError::generic_err(
+            "This function is only usable by forwarders the factory is creating",
+        ))?;
+    let mut pending_storage = PrefixedStorage::new(PREFIX_PENDING_FORWARDERS, &mut deps.storage);
+    remove(&mut pending_storage, &id_key);
+
+    let forwarder = StoredForwarder {
+        address: deps.api.canonical_address(&env.message.sender)?,
+        label: pending.label,
+        creator: pending.creator,
+        admins: pending.admins,
+        created_height: env.block.height,
+    };
+    let mut forwarder_storage = PrefixedStorage::new(PREFIX_FORWARDERS, &mut deps.storage);
+    save(&mut forwarder_storage, &id_key, &forwarder)?;
+    index_forwarder(&mut deps.storage, PREFIX_CREATOR_FORWARDERS, &forwarder.creator, forwarder_id)?;
+    for admin in forwarder.admins.iter() {
+        index_forwarder(&mut deps.storage, PREFIX_INITIAL_ADMIN_FORWARDERS, admin, forwarder_id)?;
+    }
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "register_forwarder"),
+            log("sender", &env.message.sender),
+            log("forwarder_id", forwarder_id),
+        ],
+        data: Some(to_binary(&HandleAnswer::RegisterForwarder {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+/// Changes the code of the forwarders the factory creates.  Forwarders already created are
+/// unaffected
+///
+/// # Arguements
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `code_id` - code id of the fund-forwarding contract
+/// * `code_hash` - code hash of the fund-forwarding contract
+pub fn set_forwarder_code<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    code_id: u64,
+    code_hash: String,
+) -> StdResult<HandleResponse> {
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    if deps.api.canonical_address(&env.message.sender)? != config.admin {
+        return Err(StdError::generic_err(
+            "This function is only usable by the Admin",
+        ));
+    }
+    config.forwarder_code_id = code_id;
+    config.forwarder_code_hash = code_hash;
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "set_forwarder_code"),
+            log("sender", &env.message.sender),
+            log("code_id", code_id),
+        ],
+        data: Some(to_binary(&HandleAnswer::SetForwarderCode {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+
+/// Returns StdResult<()> after adding a forwarder to an address's list of forwarders
+///
+/// # Arguments
+///
+/// * `storage` - a mutable reference to the contract's storage
+/// * `prefix` - prefix of the index to add to
+/// * `address` - a reference to the address the forwarder is listed under
+/// * `forwarder_id` - id of the forwarder
+fn index_forwarder<S: Storage>(
+    storage: &mut S,
+    prefix: &[u8],
+    address: &CanonicalAddr,
+    forwarder_id: u64,
+) -> StdResult<()> {
+    let mut index_storage = PrefixedStorage::multilevel(&[prefix, address.as_slice()], storage);
+    let mut index = AppendStoreMut::<u64, _>::attach_or_create(&mut index_storage)?;
+    index.push(&forwarder_id)
+}
+
+
+pub fn query<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    msg: QueryMsg,
+) -> QueryResult {
+    match msg {
+        QueryMsg::QueryConfig {} => query_config(deps),
+        QueryMsg::QueryForwardersByCreator { creator, page, page_size } => {
+            query_forwarders(deps, PREFIX_CREATOR_FORWARDERS, &creator, page, page_size)
+        }
+        QueryMsg::QueryForwardersByInitialAdmin { admin, page, page_size } => {
+            query_forwarders(deps, PREFIX_INITIAL_ADMIN_FORWARDERS, &admin, page, page_size)
+        }
+    }
+}
+
+
+fn query_config<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> QueryResult {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+
+    to_binary(&QueryAnswer::Config {
+        admin: deps.api.human_address(&config.admin)?,
+        forwarder_code_id: config.forwarder_code_id,
+        forwarder_code_hash: config.forwarder_code_hash,
+    })
+}
+
+
+/// Returns QueryResult displaying a page of the forwarders listed under an address, most recent
+/// first
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `prefix` - prefix of the index to list
+/// * `address` - a reference to the address whose forwarders are listed
+/// * `page` - optional page to display, starting at 0
+/// * `page_size` - number of forwarders per page
+fn query_forwarders<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    prefix: &[u8],
+    address: &HumanAddr,
+    page: Option<u32>,
+    page_size: u32,
+) -> QueryResult {
+    let address_raw = deps.api.canonical_address(address)?;
+    let index_storage = ReadonlyPrefixedStorage::multilevel(&[prefix, address_raw.as_slice()], &deps.storage);
+    let skip = page.unwrap_or(0).saturating_mul(page_size);
+
+    let index = match AppendStore::<u64, _>::attach(&index_storage) {
+        Some(index) => index?,
+        None => return to_binary(&QueryAnswer::Forwarders { forwarders: vec![], total: 0 }),
+    };
+    let total = index.len();
+    let forwarder_storage = ReadonlyPrefixedStorage::new(PREFIX_FORWARDERS, &deps.storage);
+    // only the positions on the requested page are read, most recent first
+    let forwarders = (0..total.saturating_sub(skip))
+        .rev()
+        .take(page_size as usize)
+        .map(|pos| {
+            let forwarder_id = index.get_at(pos)?;
+            let forwarder: StoredForwarder = load(&forwarder_storage, &forwarder_id.to_be_bytes())?;
+            Ok(ForwarderInfo {
+                forwarder_id,
+                address: deps.api.human_address(&forwarder.address)?,
+                label: forwarder.label,
+                creator: deps.api.human_address(&forwarder.creator)?,
+                admins: forwarder
+                    .admins
+                    .iter()
+                    .map(|a| deps.api.human_address(a))
+                    .collect::<StdResult<Vec<HumanAddr>>>()?,
+                created_height: forwarder.created_height,
+            })
+        })
+        .collect::<StdResult<Vec<ForwarderInfo>>>()?;
+
+    to_binary(&QueryAnswer::Forwarders { forwarders, total })
+}
@@ -0,0 +1,6 @@
+//! A companion contract that instantiates fund-forwarding contracts and indexes them by
+//! creator and admin.  Build it with `--features factory`
+
+pub mod contract;
+pub mod msg;
+pub mod state;
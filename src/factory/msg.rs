@@ -0,0 +1,107 @@
+use cosmwasm_std::HumanAddr;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::msg::{InitMsg as ForwarderInitMsg, ResponseStatus};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InitMsg {
+    /// address that can change the forwarder code.  Defaults to the sender
+    #[serde(default)]
+    pub admin: Option<HumanAddr>,
+    /// code id of the fund-forwarding contract
+    pub forwarder_code_id: u64,
+    /// code hash of the fund-forwarding contract
+    pub forwarder_code_hash: String,
+    /// entropy used to generate the keys forwarders call back with
+    pub entropy: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HandleMsg {
+    /// instantiates a fund-forwarding contract with the given label and init msg.  Any coins
+    /// sent are passed on to the instantiation
+    CreateForwarder {
+        label: String,
+        forwarder: ForwarderInitMsg,
+    },
+    /// records the address of a forwarder.  Only usable by the forwarder's instantiation
+    RegisterForwarder {
+        forwarder_id: u64,
+        key: String,
+    },
+    /// changes the code of the forwarders the factory creates, such as after new forwarder
+    /// code is stored.  Only usable by the admin
+    SetForwarderCode {
+        code_id: u64,
+        code_hash: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum HandleAnswer {
+    CreateForwarder {
+        /// id the forwarder is recorded under once it calls back
+        forwarder_id: u64,
+    },
+    RegisterForwarder {
+        status: ResponseStatus,
+    },
+    SetForwarderCode {
+        status: ResponseStatus,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// displays the admin and the code of the forwarders the factory creates
+    QueryConfig {},
+    /// displays the forwarders an address created, most recent first
+    QueryForwardersByCreator {
+        creator: HumanAddr,
+        /// optional page to display, starting at 0
+        #[serde(default)]
+        page: Option<u32>,
+        page_size: u32,
+    },
+    /// displays the forwarders an address was made an admin of when they were created, most
+    /// recent first.  Admin changes made later in a forwarder are not tracked
+    QueryForwardersByInitialAdmin {
+        admin: HumanAddr,
+        /// optional page to display, starting at 0
+        #[serde(default)]
+        page: Option<u32>,
+        page_size: u32,
+    },
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryAnswer {
+    Config {
+        admin: HumanAddr,
+        forwarder_code_id: u64,
+        forwarder_code_hash: String,
+    },
+    Forwarders {
+        forwarders: Vec<ForwarderInfo>,
+        /// total number of forwarders listed for the address
+        total: u32,
+    },
+}
+
+/// display for a forwarder
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ForwarderInfo {
+    pub forwarder_id: u64,
+    pub address: HumanAddr,
+    pub label: String,
+    pub creator: HumanAddr,
+    /// admins the forwarder was instantiated with
+    pub admins: Vec<HumanAddr>,
+    /// block height when the forwarder was instantiated
+    pub created_height: u64,
+}
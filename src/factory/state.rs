@@ -0,0 +1,49 @@
+use cosmwasm_std::CanonicalAddr;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+pub const CONFIG_KEY: &[u8] = b"config";
+pub const PRNG_SEED_KEY: &[u8] = b"prngseed";
+/// storage key for the number of forwarders created
+pub const FORWARDER_COUNT_KEY: &[u8] = b"forwardercount";
+/// prefix for the forwarders being instantiated, keyed by forwarder id
+pub const PREFIX_PENDING_FORWARDERS: &[u8] = b"pendingforwarders";
+/// prefix for the instantiated forwarders, keyed by forwarder id
+pub const PREFIX_FORWARDERS: &[u8] = b"forwarders";
+/// prefix for the ids of the forwarders each address created
+pub const PREFIX_CREATOR_FORWARDERS: &[u8] = b"creatorforwarders";
+/// prefix for the ids of the forwarders each address was made an admin of at creation
+pub const PREFIX_INITIAL_ADMIN_FORWARDERS: &[u8] = b"adminforwarders";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    /// address that can change the forwarder code
+    pub admin: CanonicalAddr,
+    /// code id of the fund-forwarding contract
+    pub forwarder_code_id: u64,
+    /// code hash of the fund-forwarding contract
+    pub forwarder_code_hash: String,
+}
+
+/// a forwarder whose instantiation has not called back yet
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StoredPendingForwarder {
+    pub label: String,
+    pub creator: CanonicalAddr,
+    /// admins the forwarder was instantiated with
+    pub admins: Vec<CanonicalAddr>,
+    /// hash of the key the forwarder must call back with
+    pub key_hash: [u8; 32],
+}
+
+/// an instantiated forwarder
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StoredForwarder {
+    pub address: CanonicalAddr,
+    pub label: String,
+    pub creator: CanonicalAddr,
+    /// admins the forwarder was instantiated with
+    pub admins: Vec<CanonicalAddr>,
+    /// block height when the forwarder called back
+    pub created_height: u64,
+}
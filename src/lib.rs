@@ -1,12 +1,17 @@
 pub mod contract;
+pub mod factory;
 pub mod msg;
 pub mod state;
 pub mod royalties;
 pub mod unittest;
+pub mod viewing_key;
 
 #[cfg(target_arch = "wasm32")]
 mod wasm {
+    #[cfg(not(feature = "factory"))]
     use super::contract;
+    #[cfg(feature = "factory")]
+    use super::factory::contract;
     use cosmwasm_std::{
         do_handle, do_init, do_query, ExternalApi, ExternalQuerier, ExternalStorage,
     };
@@ -1,16 +1,71 @@
-use cosmwasm_std::{Binary, HumanAddr, Uint128};
+use cosmwasm_std::{Binary, Coin, HumanAddr, StdError, StdResult, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use secret_toolkit::permit::Permit;
 use crate::royalties::{DisplayRoyaltyInfo, RoyaltyInfo};
+use crate::state::{Activation, DistributionMode, PendingTimelock, RegistrationPolicy, StoredPayoutRecord, Timelock};
+use crate::viewing_key::ViewingKey;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InitMsg {
     pub admin: HumanAddr,
+    /// other addresses that share the admin role
+    #[serde(default)]
+    pub co_admins: Vec<HumanAddr>,
+    /// number of admins that must approve an admin action.  Defaults to 1
+    #[serde(default)]
+    pub admin_threshold: Option<u16>,
 
     pub dist_info: RoyaltyInfo,
     
     pub sscrt_addr: HumanAddr,
     pub sscrt_hash: String,
+
+    /// whether received snip20s are sent to recipients or held for them to claim.
+    /// Defaults to push
+    #[serde(default)]
+    pub distribution_mode: DistributionMode,
+
+    /// entropy used to generate viewing keys
+    pub prng_seed: Binary,
+
+    /// true if the public distribution query should hide recipient addresses
+    #[serde(default)]
+    pub hide_recipients: bool,
+
+    /// optional delay before distribution changes take effect
+    #[serde(default)]
+    pub timelock: Option<Timelock>,
+
+    /// true if lowering a recipient's rate requires that recipient's approval
+    #[serde(default)]
+    pub locked_recipients: bool,
+
+    /// who besides the admin can register tokens.  Defaults to admin only
+    #[serde(default)]
+    pub registration_policy: RegistrationPolicy,
+
+    /// number of blocks in each stats epoch.  Defaults to 100,000
+    #[serde(default)]
+    pub stats_epoch_length: Option<u64>,
+
+    /// number of forwarders received funds can have passed through before reaching this
+    /// one.  Defaults to 10
+    #[serde(default)]
+    pub max_forward_depth: Option<u32>,
+
+    /// message executed on another contract once this one is instantiated, such as the
+    /// factory that created it
+    #[serde(default)]
+    pub callback: Option<Callback>,
+}
+
+/// a message to execute on a contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Callback {
+    pub msg: Binary,
+    pub contract_addr: HumanAddr,
+    pub code_hash: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -25,20 +80,646 @@ pub enum HandleMsg {
     },
     RegisterToken {
         snip20_addr: HumanAddr,
-        snip20_hash: String
+        snip20_hash: String,
+        /// optional distribution used for this token instead of the default
+        #[serde(default)]
+        dist_info: Option<RoyaltyInfo>,
+    },
+    /// sets the distribution used for a registered token.  If dist_info is not provided,
+    /// the token will use the default distribution
+    SetTokenDistribution {
+        snip20_addr: HumanAddr,
+        dist_info: Option<RoyaltyInfo>,
+    },
+    /// stops accepting a token.  Claimable balances of it can still be withdrawn
+    UnregisterToken {
+        snip20_addr: HumanAddr,
+    },
+    /// sends tokens held by the contract to a recipient.  Amounts owed to recipients can not
+    /// be swept
+    Sweep {
+        token: HumanAddr,
+        amount: Uint128,
+        recipient: HumanAddr,
+    },
+    /// sends native coins held by the contract to a recipient
+    SweepNative {
+        amount: Vec<Coin>,
+        recipient: HumanAddr,
+    },
+    /// splits the contract's balance of a token, less any amounts owed to recipients, using
+    /// the token's distribution
+    RedistributeBalance {
+        token: HumanAddr,
+    },
+    /// replaces the viewing key the contract uses to query its balance of a token
+    SetTokenViewingKey {
+        token: HumanAddr,
+        key: String,
+    },
+    /// splits the native coins sent with this message using the default distribution, or the
+    /// named distribution or split if one is specified.  Native coins can not be claimed, so
+    /// this fails in pull mode
+    ForwardNative {
+        #[serde(default)]
+        distribution: Option<String>,
+        #[serde(default)]
+        split_id: Option<u64>,
+    },
+    /// creates a split with its own admin and distribution, which Receive msgs can select by
+    /// the split id it answers.  Anyone can create a split.  If no admin is specified, the
+    /// sender is the admin
+    CreateSplit {
+        name: String,
+        dist_info: RoyaltyInfo,
+        #[serde(default)]
+        admin: Option<HumanAddr>,
+    },
+    /// changes a split's distribution.  Only usable by the split's admin
+    ChangeSplitDistribution {
+        split_id: u64,
+        dist_info: RoyaltyInfo,
+    },
+    /// proposes a new admin for a split, who must accept before becoming its admin.  Only
+    /// usable by the split's admin
+    ProposeSplitAdmin {
+        split_id: u64,
+        admin_addr: HumanAddr,
+    },
+    /// accepts the admin role of a split proposed to the sender
+    AcceptSplitAdmin {
+        split_id: u64,
     },
     ChangeDistribution {
         dist_info: RoyaltyInfo,
     },
-    ChangeAdmin {
+    /// sets a named distribution that Receive msgs can select.  If dist_info is not
+    /// provided, the named distribution is removed
+    SetNamedDistribution {
+        name: String,
+        dist_info: Option<RoyaltyInfo>,
+    },
+    /// proposes a new admin, who must accept before becoming admin
+    ProposeAdmin {
         admin_addr: HumanAddr,
+    },
+    /// accepts the admin role proposed to the sender
+    AcceptAdmin {},
+    /// cancels a pending admin proposal
+    CancelAdminProposal {},
+    /// replaces the admins and the number of them that must approve admin actions
+    ChangeAdmins {
+        admins: Vec<HumanAddr>,
+        threshold: u16,
+    },
+    /// approves an admin action, which is performed once enough admins approve it
+    ApproveProposal {
+        proposal_id: u64,
+    },
+    SetDistributionMode {
+        mode: DistributionMode,
+    },
+    /// sets whether the public distribution query hides recipient addresses
+    SetHideRecipients {
+        hide_recipients: bool,
+    },
+    /// sets the delay before distribution changes take effect.  A shorter delay only takes
+    /// effect once the current delay has passed
+    SetTimelock {
+        timelock: Option<Timelock>,
+    },
+    /// cancels the pending change to the specified token's or named distribution, or to the
+    /// default distribution if neither is specified
+    CancelDistributionChange {
+        #[serde(default)]
+        token: Option<HumanAddr>,
+        #[serde(default)]
+        distribution: Option<String>,
+    },
+    /// requires the approval of every recipient whose rate a distribution change lowers.
+    /// Once locked, recipients can not be unlocked
+    LockRecipients {},
+    /// sets who besides the admins can register tokens
+    SetRegistrationPolicy {
+        policy: RegistrationPolicy,
+    },
+    /// sets the number of forwarders received funds can have passed through before reaching
+    /// this one
+    SetMaxForwardDepth {
+        max_forward_depth: u32,
+    },
+    /// approves a distribution change that lowers the sender's rate, which is made once
+    /// every such recipient approves it
+    ApproveDistributionChange {
+        proposal_id: u64,
+    },
+    /// moves the sender's share of every distribution to a new address.  If
+    /// require_acceptance is true, the move is made once the new address accepts it
+    ChangeMyRecipientAddress {
+        new_addr: HumanAddr,
+        #[serde(default)]
+        require_acceptance: bool,
+    },
+    /// accepts the share of old_addr that it asked to move to the sender
+    AcceptRecipientAddress {
+        old_addr: HumanAddr,
+    },
+    /// withdraws the sender's claimable balances of the listed tokens
+    Claim {
+        tokens: Vec<HumanAddr>,
+    },
+    CreateViewingKey {
+        entropy: String,
+        #[serde(default)]
+        padding: Option<String>,
+    },
+    SetViewingKey {
+        key: String,
+        #[serde(default)]
+        padding: Option<String>,
+    },
+    /// disallows the use of a permit
+    RevokePermit {
+        permit_name: String,
+        #[serde(default)]
+        padding: Option<String>,
+    },
+}
+
+/// an action only admins can perform.  If more than one admin must approve, it becomes a
+/// proposal that is performed once enough admins approve it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminAction {
+    RegisterToken {
+        snip20_addr: HumanAddr,
+        snip20_hash: String,
+        dist_info: Option<RoyaltyInfo>,
+    },
+    SetTokenDistribution {
+        snip20_addr: HumanAddr,
+        dist_info: Option<RoyaltyInfo>,
+    },
+    ChangeDistribution {
+        dist_info: RoyaltyInfo,
+    },
+    SetNamedDistribution {
+        name: String,
+        dist_info: Option<RoyaltyInfo>,
+    },
+    ProposeAdmin {
+        admin_addr: HumanAddr,
+    },
+    CancelAdminProposal {},
+    ChangeAdmins {
+        admins: Vec<HumanAddr>,
+        threshold: u16,
+    },
+    SetDistributionMode {
+        mode: DistributionMode,
+    },
+    SetHideRecipients {
+        hide_recipients: bool,
+    },
+    SetTimelock {
+        timelock: Option<Timelock>,
+    },
+    CancelDistributionChange {
+        token: Option<HumanAddr>,
+        distribution: Option<String>,
+    },
+    LockRecipients {},
+    SetRegistrationPolicy {
+        policy: RegistrationPolicy,
+    },
+    SetMaxForwardDepth {
+        max_forward_depth: u32,
+    },
+    UnregisterToken {
+        snip20_addr: HumanAddr,
+    },
+    Sweep {
+        token: HumanAddr,
+        amount: Uint128,
+        recipient: HumanAddr,
+    },
+    SweepNative {
+        amount: Vec<Coin>,
+        recipient: HumanAddr,
+    },
+    RedistributeBalance {
+        token: HumanAddr,
+    },
+    SetTokenViewingKey {
+        token: HumanAddr,
+        key: String,
+    },
+}
+
+impl AdminAction {
+    /// Returns &'static str of the name of the action, as logged
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::RegisterToken { .. } => "register_token",
+            Self::SetTokenDistribution { .. } => "set_token_distribution",
+            Self::ChangeDistribution { .. } => "change_distribution",
+            Self::SetNamedDistribution { .. } => "set_named_distribution",
+            Self::ProposeAdmin { .. } => "propose_admin",
+            Self::CancelAdminProposal {} => "cancel_admin_proposal",
+            Self::ChangeAdmins { .. } => "change_admins",
+            Self::SetDistributionMode { .. } => "set_distribution_mode",
+            Self::SetHideRecipients { .. } => "set_hide_recipients",
+            Self::SetTimelock { .. } => "set_timelock",
+            Self::CancelDistributionChange { .. } => "cancel_distribution_change",
+            Self::LockRecipients {} => "lock_recipients",
+            Self::SetRegistrationPolicy { .. } => "set_registration_policy",
+            Self::SetMaxForwardDepth { .. } => "set_max_forward_depth",
+            Self::UnregisterToken { .. } => "unregister_token",
+            Self::Sweep { .. } => "sweep",
+            Self::SweepNative { .. } => "sweep_native",
+            Self::RedistributeBalance { .. } => "redistribute_balance",
+            Self::SetTokenViewingKey { .. } => "set_token_viewing_key",
+        }
     }
 }
 
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum HandleAnswer {
+    /// funds split among the recipients by Receive or ForwardNative
+    ForwardFunds {
+        /// one forward per snip20 or native denom
+        forwards: Vec<ForwardSummary>,
+    },
+    RegisterToken {
+        status: ResponseStatus,
+    },
+    SetTokenDistribution {
+        status: ResponseStatus,
+    },
+    UnregisterToken {
+        status: ResponseStatus,
+    },
+    Sweep {
+        status: ResponseStatus,
+    },
+    SweepNative {
+        status: ResponseStatus,
+    },
+    RedistributeBalance {
+        forward: ForwardSummary,
+    },
+    SetTokenViewingKey {
+        status: ResponseStatus,
+    },
+    ChangeDistribution {
+        status: ResponseStatus,
+    },
+    SetNamedDistribution {
+        status: ResponseStatus,
+    },
+    CreateSplit {
+        split_id: u64,
+    },
+    ChangeSplitDistribution {
+        status: ResponseStatus,
+    },
+    ProposeSplitAdmin {
+        status: ResponseStatus,
+    },
+    AcceptSplitAdmin {
+        status: ResponseStatus,
+    },
+    ProposeAdmin {
+        status: ResponseStatus,
+    },
+    AcceptAdmin {
+        status: ResponseStatus,
+    },
+    CancelAdminProposal {
+        status: ResponseStatus,
+    },
+    ChangeAdmins {
+        status: ResponseStatus,
+    },
+    SetDistributionMode {
+        status: ResponseStatus,
+    },
+    SetHideRecipients {
+        status: ResponseStatus,
+    },
+    SetTimelock {
+        status: ResponseStatus,
+    },
+    CancelDistributionChange {
+        status: ResponseStatus,
+    },
+    LockRecipients {
+        status: ResponseStatus,
+    },
+    SetRegistrationPolicy {
+        status: ResponseStatus,
+    },
+    SetMaxForwardDepth {
+        status: ResponseStatus,
+    },
+    ChangeMyRecipientAddress {
+        status: ResponseStatus,
+    },
+    AcceptRecipientAddress {
+        status: ResponseStatus,
+    },
+    Claim {
+        /// amounts withdrawn
+        claimed: Vec<TokenBalance>,
+    },
+    CreateViewingKey {
+        key: ViewingKey,
+    },
+    SetViewingKey {
+        status: ResponseStatus,
+    },
+    RevokePermit {
+        status: ResponseStatus,
+    },
+    /// an admin action still awaiting approval
+    Proposal {
+        proposal_id: u64,
+        approvals: u16,
+    },
+    /// a distribution change still awaiting the approval of the recipients it lowers
+    DistributionProposal {
+        proposal_id: u64,
+        /// number of recipients yet to approve
+        awaiting: u16,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseStatus {
+    Success,
+    Failure,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    QueryDist {}
+    /// displays the distribution used for the specified token, the named distribution, or
+    /// the default distribution if neither is specified
+    QueryDist {
+        #[serde(default)]
+        token: Option<HumanAddr>,
+        #[serde(default)]
+        distribution: Option<String>,
+        /// current block height, so a change whose timelock has passed is shown in use
+        #[serde(default)]
+        height: Option<u64>,
+        /// current block time in seconds since epoch, so a change whose timelock has passed
+        /// is shown in use
+        #[serde(default)]
+        time: Option<u64>,
+    },
+    /// displays the contract configuration
+    QueryConfig {},
+    /// displays the registered tokens, in registration order
+    QueryRegisteredTokens {
+        /// optional page to display, starting at 0
+        #[serde(default)]
+        page: Option<u32>,
+        page_size: u32,
+    },
+    /// displays the splits, in creation order
+    QuerySplits {
+        /// optional page to display, starting at 0
+        #[serde(default)]
+        page: Option<u32>,
+        page_size: u32,
+    },
+    /// displays a split
+    QuerySplit {
+        split_id: u64,
+    },
+    /// displays the balances of the listed tokens the address can claim
+    QueryClaimable {
+        address: HumanAddr,
+        key: String,
+        tokens: Vec<HumanAddr>,
+    },
+    /// displays the address's share of the specified token's or named distribution, or of
+    /// the default distribution if neither is specified
+    QueryShare {
+        address: HumanAddr,
+        key: String,
+        #[serde(default)]
+        token: Option<HumanAddr>,
+        #[serde(default)]
+        distribution: Option<String>,
+        /// current block height, so a change whose timelock has passed is shown in use
+        #[serde(default)]
+        height: Option<u64>,
+        /// current block time in seconds since epoch, so a change whose timelock has passed
+        /// is shown in use
+        #[serde(default)]
+        time: Option<u64>,
+    },
+    /// displays the distribution used for the specified token, the named distribution, or
+    /// the default distribution if neither is specified.  The admin sees every address, and
+    /// recipients see only their own
+    QueryPrivateDist {
+        address: HumanAddr,
+        key: String,
+        #[serde(default)]
+        token: Option<HumanAddr>,
+        #[serde(default)]
+        distribution: Option<String>,
+        /// current block height, so a change whose timelock has passed is shown in use
+        #[serde(default)]
+        height: Option<u64>,
+        /// current block time in seconds since epoch, so a change whose timelock has passed
+        /// is shown in use
+        #[serde(default)]
+        time: Option<u64>,
+    },
+    /// displays a page of the admin proposals, most recent first, including executed ones.
+    /// Only usable by admins
+    QueryProposals {
+        address: HumanAddr,
+        key: String,
+        /// optional page to display, starting at 0
+        #[serde(default)]
+        page: Option<u32>,
+        page_size: u32,
+    },
+    /// displays an admin proposal.  Only usable by admins
+    QueryProposal {
+        address: HumanAddr,
+        key: String,
+        proposal_id: u64,
+    },
+    /// displays a distribution change awaiting recipient approval.  Usable by admins and the
+    /// recipients whose rates it lowers
+    QueryDistributionProposal {
+        address: HumanAddr,
+        key: String,
+        proposal_id: u64,
+    },
+    /// displays the contract's balance of every registered token.  Only usable by admins
+    QueryContractBalances {
+        address: HumanAddr,
+        key: String,
+    },
+    /// displays the lifetime totals distributed to the address for every token, whether
+    /// transferred or credited to its claimable balance
+    QueryEarnings {
+        address: HumanAddr,
+        key: String,
+    },
+    /// displays the address's payouts, most recent first
+    QueryPayouts {
+        address: HumanAddr,
+        key: String,
+        /// optional snip20 contract address or native denom to only display payouts of
+        #[serde(default)]
+        token: Option<String>,
+        /// optional page to display, starting at 0
+        #[serde(default)]
+        page: Option<u32>,
+        page_size: u32,
+    },
+    /// displays the ledger of forwards, most recent first.  Only usable by admins
+    QueryForwardHistory {
+        address: HumanAddr,
+        key: String,
+        /// optional snip20 contract address or native denom to only display forwards of
+        #[serde(default)]
+        token: Option<String>,
+        /// optional page to display, starting at 0
+        #[serde(default)]
+        page: Option<u32>,
+        page_size: u32,
+    },
+    /// displays the ledger of a split's forwards, most recent first.  Only usable by the
+    /// split's admin
+    QuerySplitForwards {
+        address: HumanAddr,
+        key: String,
+        split_id: u64,
+        /// optional page to display, starting at 0
+        #[serde(default)]
+        page: Option<u32>,
+        page_size: u32,
+    },
+    /// displays the lifetime totals of a token, and optionally its totals in each epoch
+    /// overlapping a range of block heights.  Only usable by admins
+    QueryStats {
+        address: HumanAddr,
+        key: String,
+        /// snip20 contract address or native denom
+        token: String,
+        #[serde(default)]
+        epochs: Option<HeightRange>,
+    },
+    /// authenticates a query with a permit instead of a viewing key
+    WithPermit {
+        permit: Permit,
+        query: QueryWithPermit,
+    },
+}
+
+impl QueryMsg {
+    /// Returns StdResult<(&HumanAddr, &str)> of the address and viewing key a query must be
+    /// authenticated with
+    pub fn get_validation_params(&self) -> StdResult<(&HumanAddr, &str)> {
+        match self {
+            Self::QueryClaimable { address, key, .. }
+            | Self::QueryShare { address, key, .. }
+            | Self::QueryPrivateDist { address, key, .. }
+            | Self::QueryProposals { address, key, .. }
+            | Self::QueryProposal { address, key, .. }
+            | Self::QueryDistributionProposal { address, key, .. }
+            | Self::QueryContractBalances { address, key }
+            | Self::QueryEarnings { address, key }
+            | Self::QueryPayouts { address, key, .. }
+            | Self::QueryForwardHistory { address, key, .. }
+            | Self::QuerySplitForwards { address, key, .. }
+            | Self::QueryStats { address, key, .. } => Ok((address, key.as_str())),
+            _ => Err(StdError::generic_err("This query does not use a viewing key")),
+        }
+    }
+}
+
+/// queries authenticated with a permit
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryWithPermit {
+    Claimable {
+        tokens: Vec<HumanAddr>,
+    },
+    Share {
+        #[serde(default)]
+        token: Option<HumanAddr>,
+        #[serde(default)]
+        distribution: Option<String>,
+        /// current block height, so a change whose timelock has passed is shown in use
+        #[serde(default)]
+        height: Option<u64>,
+        /// current block time in seconds since epoch, so a change whose timelock has passed
+        /// is shown in use
+        #[serde(default)]
+        time: Option<u64>,
+    },
+    PrivateDist {
+        #[serde(default)]
+        token: Option<HumanAddr>,
+        #[serde(default)]
+        distribution: Option<String>,
+        /// current block height, so a change whose timelock has passed is shown in use
+        #[serde(default)]
+        height: Option<u64>,
+        /// current block time in seconds since epoch, so a change whose timelock has passed
+        /// is shown in use
+        #[serde(default)]
+        time: Option<u64>,
+    },
+    Proposals {
+        #[serde(default)]
+        page: Option<u32>,
+        page_size: u32,
+    },
+    Proposal {
+        proposal_id: u64,
+    },
+    DistributionProposal {
+        proposal_id: u64,
+    },
+    ContractBalances {},
+    Earnings {},
+    Payouts {
+        #[serde(default)]
+        token: Option<String>,
+        #[serde(default)]
+        page: Option<u32>,
+        page_size: u32,
+    },
+    ForwardHistory {
+        #[serde(default)]
+        token: Option<String>,
+        #[serde(default)]
+        page: Option<u32>,
+        page_size: u32,
+    },
+    SplitForwards {
+        split_id: u64,
+        #[serde(default)]
+        page: Option<u32>,
+        page_size: u32,
+    },
+    Stats {
+        token: String,
+        #[serde(default)]
+        epochs: Option<HeightRange>,
+    },
 }
 
 // We define a custom struct for each query response
@@ -54,6 +735,309 @@ pub struct DistributionCheckResponse {
 pub enum QueryAnswer {
     RoyaltyInfo {
         royalty_info: Option<DisplayRoyaltyInfo>,
+        /// change waiting for the timelock to pass
+        pending: Option<PendingDistribution>,
     },
+    Config {
+        admins: Vec<HumanAddr>,
+        threshold: u16,
+        pending_admin: Option<HumanAddr>,
+        distribution_mode: DistributionMode,
+        hide_recipients: bool,
+        timelock: Option<Timelock>,
+        pending_timelock: Option<PendingTimelock>,
+        locked_recipients: bool,
+        registration_policy: RegistrationPolicy,
+        /// number of blocks in each stats epoch
+        stats_epoch_length: u64,
+        /// names of the distributions Receive msgs can select
+        distributions: Vec<String>,
+        /// number of forwarders received funds can have passed through before reaching
+        /// this one
+        max_forward_depth: u32,
+    },
+    Claimable {
+        balances: Vec<TokenBalance>,
+    },
+    ContractBalances {
+        balances: Vec<TokenBalance>,
+        /// tokens whose balance could not be queried
+        errors: Vec<TokenError>,
+    },
+    Share {
+        token: Option<HumanAddr>,
+        distribution: Option<String>,
+        decimal_places_in_rates: u8,
+        /// sum of the address's rates in the distribution
+        rate: Uint128,
+    },
+    Earnings {
+        earnings: Vec<TokenTotal>,
+    },
+    Payouts {
+        payouts: Vec<PayoutRecord>,
+        /// total number of payouts to the address
+        total: u32,
+    },
+    ForwardHistory {
+        forwards: Vec<ForwardRecord>,
+        /// total number of forwards
+        total: u32,
+    },
+    Stats {
+        /// snip20 contract address or native denom
+        token: String,
+        /// total amount received and split
+        received: Uint128,
+        /// total rounding dust added to payouts
+        dust: Uint128,
+        /// number of forwards
+        forwards: u32,
+        /// total distributed to each recipient.  In pull mode this includes amounts credited
+        /// to claimable balances that have not been claimed yet
+        distributed: Vec<Payout>,
+        /// amount of the token credited to recipients in pull mode that is still waiting to
+        /// be claimed
+        unclaimed: Uint128,
+        /// number of blocks in each epoch
+        epoch_length: u64,
+        /// epochs in the requested range that had forwards, oldest first
+        epochs: Vec<EpochStats>,
+    },
+    Proposals {
+        proposals: Vec<ProposalInfo>,
+        /// total number of proposals, executed or not
+        total: u64,
+    },
+    Proposal {
+        proposal: ProposalInfo,
+    },
+    DistributionProposal {
+        proposal: DistributionProposalInfo,
+    },
+    RegisteredTokens {
+        tokens: Vec<RegisteredToken>,
+        /// total number of registered tokens
+        total: u32,
+    },
+    Splits {
+        splits: Vec<SplitInfo>,
+        /// total number of splits
+        total: u64,
+    },
+    Split {
+        split: SplitInfo,
+    },
+
+}
+
+/// balance of a single token
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenBalance {
+    pub token: HumanAddr,
+    pub amount: Uint128,
+}
+
+/// error from querying the balance of a single token
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenError {
+    pub token: HumanAddr,
+    pub error: String,
+}
+
+/// lifetime total of a single token
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenTotal {
+    /// snip20 contract address or native denom
+    pub token: String,
+    pub amount: Uint128,
+}
+
+/// display for a single payout
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PayoutRecord {
+    /// snip20 contract address or native denom
+    pub token: String,
+    pub amount: Uint128,
+    pub block_height: u64,
+    /// block time in seconds since epoch
+    pub block_time: u64,
+    /// id of the forward in the ledger
+    pub forward_id: u32,
+}
+
+impl From<StoredPayoutRecord> for PayoutRecord {
+    fn from(stored: StoredPayoutRecord) -> Self {
+        PayoutRecord {
+            token: stored.token,
+            amount: Uint128(stored.amount),
+            block_height: stored.block_height,
+            block_time: stored.block_time,
+            forward_id: stored.forward_id,
+        }
+    }
+}
+
+/// display for a single payout of a forward
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Payout {
+    pub recipient: HumanAddr,
+    pub amount: Uint128,
+}
 
-}
\ No newline at end of file
+/// range of block heights, including both ends
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HeightRange {
+    pub start_height: u64,
+    pub end_height: u64,
+}
+
+/// totals of a token in a single epoch
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EpochStats {
+    pub epoch: u64,
+    /// first block height of the epoch
+    pub start_height: u64,
+    /// last block height of the epoch
+    pub end_height: u64,
+    /// amount received and split during the epoch
+    pub received: Uint128,
+    /// rounding dust added to payouts during the epoch
+    pub dust: Uint128,
+    /// number of forwards during the epoch
+    pub forwards: u32,
+    /// amount distributed to each recipient during the epoch, whether transferred or credited
+    /// to a claimable balance
+    pub distributed: Vec<Payout>,
+}
+
+/// display for a forward made by a handle
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ForwardSummary {
+    /// id of the forward in the ledger, or in the split's ledger if a split was selected
+    pub forward_id: u32,
+    /// snip20 contract address or native denom
+    pub token: String,
+    /// amount split among the recipients
+    pub amount: Uint128,
+    /// payouts to each recipient, including the dust
+    pub payouts: Vec<ForwardPayout>,
+    /// amount left over by rounding that was added to one recipient's payout
+    pub dust: Uint128,
+}
+
+/// display for a single payout of a forward made by a handle
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ForwardPayout {
+    /// address paid.  None if recipients are hidden
+    pub recipient: Option<HumanAddr>,
+    pub amount: Uint128,
+}
+
+/// display for a forward in the ledger
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ForwardRecord {
+    pub forward_id: u32,
+    /// snip20 contract address or native denom
+    pub token: String,
+    /// address the funds came from
+    pub from: HumanAddr,
+    /// amount split among the recipients
+    pub amount: Uint128,
+    /// payouts to each recipient, including the dust
+    pub payouts: Vec<Payout>,
+    /// amount left over by rounding that was added to one recipient's payout
+    pub dust: Uint128,
+    pub block_height: u64,
+    /// block time in seconds since epoch
+    pub block_time: u64,
+}
+
+/// display for a distribution change waiting for the timelock to pass
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingDistribution {
+    /// the new distribution.  None means the token will use the default distribution
+    pub royalty_info: Option<DisplayRoyaltyInfo>,
+    /// when the change takes effect
+    pub activation: Activation,
+}
+
+/// display for an admin proposal
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProposalInfo {
+    pub proposal_id: u64,
+    pub action: AdminAction,
+    /// admins who approved the action
+    pub approvals: Vec<HumanAddr>,
+    /// true once the action has been performed
+    pub executed: bool,
+    /// block height when the proposal was created
+    pub created_height: u64,
+}
+
+/// payload of a snip20 Send.  This is intentionally strict: unknown fields are rejected, so a
+/// payload meant for another contract, or a misspelled selection, fails the Send instead of
+/// being split with the token's distribution
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ReceiveMsg {
+    /// named distribution to split the funds with instead of the token's
+    #[serde(default)]
+    pub distribution: Option<String>,
+    /// split to split the funds with instead of the token's distribution
+    #[serde(default)]
+    pub split_id: Option<u64>,
+    /// number of forwarders the funds have passed through
+    #[serde(default)]
+    pub hops: u32,
+}
+
+/// display for a split
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SplitInfo {
+    pub split_id: u64,
+    pub name: String,
+    pub admin: HumanAddr,
+    /// address proposed as the split's new admin
+    pub pending_admin: Option<HumanAddr>,
+    pub royalty_info: DisplayRoyaltyInfo,
+    /// change waiting for the timelock to pass
+    pub pending: Option<PendingDistribution>,
+    /// block height when the split was created
+    pub created_height: u64,
+}
+
+/// display for a registered snip20 contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RegisteredToken {
+    pub address: HumanAddr,
+    pub code_hash: String,
+    pub name: String,
+    pub symbol: String,
+    /// number of decimals in token amounts
+    pub decimals: u8,
+    /// block height when the token was registered
+    pub registered_height: u64,
+}
+
+/// display for a distribution change awaiting recipient approval
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DistributionProposalInfo {
+    pub proposal_id: u64,
+    /// token whose distribution is changed, or None for the default or a named distribution
+    pub token: Option<HumanAddr>,
+    /// named distribution that is changed
+    pub distribution: Option<String>,
+    /// split that is changed
+    pub split_id: Option<u64>,
+    /// the new distribution.  None means the token will use the default distribution
+    pub royalty_info: Option<DisplayRoyaltyInfo>,
+    /// recipients whose rates are lowered
+    pub required: Vec<HumanAddr>,
+    /// recipients who approved the change
+    pub approvals: Vec<HumanAddr>,
+    /// true once the change has been made
+    pub executed: bool,
+    /// block height when the proposal was created
+    pub created_height: u64,
+}
@@ -1,4 +1,5 @@
-use cosmwasm_std::{Api, CanonicalAddr, HumanAddr, StdResult};
+use cosmwasm_std::{Api, Binary, CanonicalAddr, HumanAddr, StdError, StdResult};
+use primitive_types::U256;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -10,6 +11,13 @@ pub struct Royalty {
     pub recipient: HumanAddr,
     /// royalty rate
     pub rate: u16,
+    /// code hash of a contract recipient.  If provided, snip20 royalties are sent with Send
+    /// so the recipient's Receive is called
+    #[serde(default)]
+    pub code_hash: Option<String>,
+    /// optional msg included in the Send to a contract recipient
+    #[serde(default)]
+    pub msg: Option<Binary>,
 }
 
 impl Royalty {
@@ -22,6 +30,8 @@ impl Royalty {
         Ok(StoredRoyalty {
             recipient: api.canonical_address(&self.recipient)?,
             rate: self.rate,
+            code_hash: self.code_hash.clone(),
+            msg: self.msg.clone(),
         })
     }
 }
@@ -33,6 +43,10 @@ pub struct RoyaltyInfo {
     pub decimal_places_in_rates: u8,
     /// list of royalties
     pub royalties: Vec<Royalty>,
+    /// recipient of any rounding dust.  If not provided, the recipient with the largest
+    /// rate receives it
+    #[serde(default)]
+    pub remainder_recipient: Option<HumanAddr>,
 }
 
 impl RoyaltyInfo {
@@ -49,6 +63,11 @@ impl RoyaltyInfo {
                 .iter()
                 .map(|r| r.to_stored(api))
                 .collect::<StdResult<Vec<StoredRoyalty>>>()?,
+            remainder_recipient: self
+                .remainder_recipient
+                .as_ref()
+                .map(|r| api.canonical_address(r))
+                .transpose()?,
         })
     }
 }
@@ -69,6 +88,8 @@ pub struct DisplayRoyaltyInfo {
     pub decimal_places_in_rates: u8,
     /// list of royalties
     pub royalties: Vec<DisplayRoyalty>,
+    /// recipient of any rounding dust.  Can be None to keep addresses private
+    pub remainder_recipient: Option<HumanAddr>,
 }
 
 /// data for storing a single royalty
@@ -78,6 +99,10 @@ pub struct StoredRoyalty {
     pub recipient: CanonicalAddr,
     /// royalty rate
     pub rate: u16,
+    /// code hash of a contract recipient
+    pub code_hash: Option<String>,
+    /// optional msg included in the Send to a contract recipient
+    pub msg: Option<Binary>,
 }
 
 impl StoredRoyalty {
@@ -107,6 +132,8 @@ impl StoredRoyalty {
         Ok(Royalty {
             recipient: api.human_address(&self.recipient)?,
             rate: self.rate,
+            code_hash: self.code_hash.clone(),
+            msg: self.msg.clone(),
         })
     }
 }
@@ -118,6 +145,26 @@ pub struct StoredRoyaltyInfo {
     pub decimal_places_in_rates: u8,
     /// list of royalties
     pub royalties: Vec<StoredRoyalty>,
+    /// recipient of any rounding dust
+    pub remainder_recipient: Option<CanonicalAddr>,
+}
+
+/// data for a single payout
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct StoredPayout {
+    /// address the payout is sent to
+    pub recipient: CanonicalAddr,
+    /// amount paid
+    pub amount: u128,
+}
+
+/// result of splitting an amount among the royalties
+#[derive(Clone, PartialEq, Debug)]
+pub struct Split {
+    /// payouts in the same order as the royalties, with the dust already included
+    pub payouts: Vec<StoredPayout>,
+    /// amount left over by integer division that was added to the dust recipient's payout
+    pub dust: u128,
 }
 
 impl StoredRoyaltyInfo {
@@ -135,6 +182,41 @@ impl StoredRoyaltyInfo {
                 .iter()
                 .map(|r| r.to_human(api, hide_addr))
                 .collect::<StdResult<Vec<DisplayRoyalty>>>()?,
+            remainder_recipient: if hide_addr {
+                None
+            } else {
+                self.remainder_recipient
+                    .as_ref()
+                    .map(|r| api.human_address(r))
+                    .transpose()?
+            },
+        })
+    }
+    /// Returns StdResult<DisplayRoyaltyInfo> from creating a DisplayRoyaltyInfo from a
+    /// StoredRoyaltyInfo where only the viewer's own address is shown
+    ///
+    /// # Arguments
+    ///
+    /// * `api` - a reference to the Api used to convert human and canonical addresses
+    /// * `viewer` - a reference to the address allowed to see itself
+    pub fn to_human_for<A: Api>(
+        &self,
+        api: &A,
+        viewer: &CanonicalAddr,
+    ) -> StdResult<DisplayRoyaltyInfo> {
+        Ok(DisplayRoyaltyInfo {
+            decimal_places_in_rates: self.decimal_places_in_rates,
+            royalties: self
+                .royalties
+                .iter()
+                .map(|r| r.to_human(api, r.recipient != *viewer))
+                .collect::<StdResult<Vec<DisplayRoyalty>>>()?,
+            remainder_recipient: self
+                .remainder_recipient
+                .as_ref()
+                .filter(|r| *r == viewer)
+                .map(|r| api.human_address(r))
+                .transpose()?,
         })
     }
     /// Returns StdResult<RoyaltyInfo> from creating a RoyaltyInfo from a StoredRoyaltyInfo
@@ -150,6 +232,121 @@ impl StoredRoyaltyInfo {
                 .iter()
                 .map(|r| r.to_human_old(api))
                 .collect::<StdResult<Vec<Royalty>>>()?,
+            remainder_recipient: self
+                .remainder_recipient
+                .as_ref()
+                .map(|r| api.human_address(r))
+                .transpose()?,
         })
     }
+
+    /// Returns StdResult<Split> from dividing an amount among the royalties.  Every share is
+    /// rounded down, and whatever is left over goes to the remainder recipient, or to the
+    /// royalty with the largest rate if there is none, so the payouts always sum to `amount`
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` - the amount to divide
+    pub fn split(&self, amount: u128) -> StdResult<Split> {
+        let (denominator, overflow) =
+            U256::from(10).overflowing_pow(U256::from(self.decimal_places_in_rates));
+        if overflow {
+            return Err(StdError::generic_err(
+                "The number of decimal places used in the royalty rates is larger than supported",
+            ));
+        }
+        let mut payouts: Vec<StoredPayout> = Vec::new();
+        let mut distributed: u128 = 0;
+        for royalty in self.royalties.iter() {
+            // rates never sum to more than the denominator, so each share fits in a u128
+            let share = (U256::from(amount) * U256::from(royalty.rate) / denominator).as_u128();
+            distributed += share;
+            payouts.push(StoredPayout {
+                recipient: royalty.recipient.clone(),
+                amount: share,
+            });
+        }
+        let dust = amount - distributed;
+        if dust > 0 {
+            let dust_idx = self
+                .remainder_recipient
+                .as_ref()
+                .and_then(|r| self.royalties.iter().position(|roy| roy.recipient == *r))
+                .or_else(|| {
+                    // first royalty with the largest rate
+                    self.royalties
+                        .iter()
+                        .enumerate()
+                        .fold(None, |max: Option<(usize, u16)>, (i, roy)| match max {
+                            Some((_, rate)) if rate >= roy.rate => max,
+                            _ => Some((i, roy.rate)),
+                        })
+                        .map(|(i, _)| i)
+                })
+                .ok_or_else(|| {
+                    StdError::generic_err("There are no royalty recipients to receive the funds")
+                })?;
+            payouts[dust_idx].amount += dust;
+        }
+        Ok(Split { payouts, dust })
+    }
+
+    /// Returns Vec<CanonicalAddr> of the recipients whose rate is lowered, or who are removed,
+    /// by replacing this distribution with a new one
+    ///
+    /// # Arguments
+    ///
+    /// * `new` - a reference to the new distribution
+    pub fn lowered_recipients(&self, new: &StoredRoyaltyInfo) -> Vec<CanonicalAddr> {
+        // rates are compared at the same number of decimal places.  Stored rates sum to
+        // 10^decimal_places, which fits in a u128, so the products fit in a U256
+        let old_scale = U256::from(10).pow(U256::from(new.decimal_places_in_rates));
+        let new_scale = U256::from(10).pow(U256::from(self.decimal_places_in_rates));
+        let mut lowered: Vec<CanonicalAddr> = Vec::new();
+        for royalty in self.royalties.iter() {
+            if lowered.contains(&royalty.recipient) {
+                continue;
+            }
+            let old_rate = U256::from(self.rate_of(&royalty.recipient)) * old_scale;
+            let new_rate = U256::from(new.rate_of(&royalty.recipient)) * new_scale;
+            if old_rate > new_rate {
+                lowered.push(royalty.recipient.clone());
+            }
+        }
+        lowered
+    }
+
+    /// Returns bool, true if the recipient was found and replaced by the new address.  The new
+    /// address is paid with transfers, since it may not be a contract
+    ///
+    /// # Arguments
+    ///
+    /// * `old` - a reference to the address being replaced
+    /// * `new` - a reference to the address replacing it
+    pub fn replace_recipient(&mut self, old: &CanonicalAddr, new: &CanonicalAddr) -> bool {
+        let mut found = false;
+        for royalty in self.royalties.iter_mut().filter(|r| r.recipient == *old) {
+            royalty.recipient = new.clone();
+            royalty.code_hash = None;
+            royalty.msg = None;
+            found = true;
+        }
+        if self.remainder_recipient.as_ref() == Some(old) {
+            self.remainder_recipient = Some(new.clone());
+        }
+        found
+    }
+
+    /// Returns u128 of the total rate paid to a recipient
+    ///
+    /// # Arguments
+    ///
+    /// * `recipient` - a reference to the recipient's address
+    pub fn rate_of(&self, recipient: &CanonicalAddr) -> u128 {
+        self.royalties
+            .iter()
+            .filter(|r| r.recipient == *recipient)
+            .map(|r| r.rate as u128)
+            .sum()
+    }
 }
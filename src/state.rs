@@ -7,20 +7,387 @@ use secret_toolkit::{
 };
 
 
-use cosmwasm_std::{CanonicalAddr, ReadonlyStorage, StdError, StdResult, Storage};
+use cosmwasm_std::{BlockInfo, CanonicalAddr, HumanAddr, ReadonlyStorage, StdError, StdResult, Storage};
+use crate::msg::AdminAction;
+use crate::royalties::{StoredPayout, StoredRoyaltyInfo};
 //use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
 
 pub const CONFIG_KEY: &[u8] = b"config";
 pub const PREFIX_TOKEN_CONTRACT_INFO: &[u8] = b"tokeninfo";
 /// Fund distribution info
 pub const FUNDS_DISTRIBUTION_KEY: &[u8] = b"fundsdistribution";
+/// prefix for fund distribution info of tokens that do not use the default
+pub const PREFIX_TOKEN_DISTRIBUTION: &[u8] = b"tokendistribution";
+/// prefix for the balances recipients can claim in pull mode
+pub const PREFIX_CLAIMABLE: &[u8] = b"claimable";
+/// prefix for the total claimable balance of each token, owed to all recipients
+pub const PREFIX_TOTAL_CLAIMABLE: &[u8] = b"totalclaimable";
+/// prefix for the viewing key the contract set on each token
+pub const PREFIX_TOKEN_VIEWING_KEY: &[u8] = b"tokenviewingkey";
+/// storage key for the prng seed
+pub const PRNG_SEED_KEY: &[u8] = b"prngseed";
+/// prefix for hashed viewing keys
+pub const PREFIX_VIEW_KEY: &[u8] = b"viewingkey";
+/// prefix for revoked permits
+pub const PREFIX_REVOKED_PERMITS: &str = "revokedpermits";
+/// prefix for each recipient's lifetime total paid per token
+pub const PREFIX_EARNINGS: &[u8] = b"earnings";
+/// prefix for the list of tokens each recipient has been paid in
+pub const PREFIX_EARNED_TOKENS: &[u8] = b"earnedtokens";
+/// prefix for each recipient's payout history
+pub const PREFIX_PAYOUT_HISTORY: &[u8] = b"payouthistory";
+/// prefix for each recipient's positions in its payout history, per token
+pub const PREFIX_TOKEN_PAYOUT_HISTORY: &[u8] = b"tokenpayouthistory";
+/// prefix for the ledger of every forward
+pub const PREFIX_FORWARD_LEDGER: &[u8] = b"forwardledger";
+/// prefix for the ledger ids of the forwards of each token
+pub const PREFIX_TOKEN_FORWARDS: &[u8] = b"tokenforwards";
+/// prefix for admin proposals awaiting approval
+pub const PREFIX_PROPOSALS: &[u8] = b"proposals";
+/// storage key for the number of admin proposals created
+pub const PROPOSAL_COUNT_KEY: &[u8] = b"proposalcount";
+/// storage key for the timelocked change to the default distribution
+pub const PENDING_DISTRIBUTION_KEY: &[u8] = b"pendingdistribution";
+/// prefix for timelocked changes to token distributions
+pub const PREFIX_PENDING_TOKEN_DISTRIBUTION: &[u8] = b"pendingtokendistribution";
+/// prefix for distribution changes awaiting recipient consent
+pub const PREFIX_DIST_PROPOSALS: &[u8] = b"distproposals";
+/// storage key for the number of distribution change proposals created
+pub const DIST_PROPOSAL_COUNT_KEY: &[u8] = b"distproposalcount";
+/// storage key for the list of every token address ever registered, in registration order
+pub const REGISTERED_TOKENS_KEY: &[u8] = b"registeredtokens";
+/// prefix for recipient address changes awaiting acceptance by the new address
+pub const PREFIX_PENDING_RECIPIENT: &[u8] = b"pendingrecipient";
+/// prefix for the lifetime totals of each token
+pub const PREFIX_TOKEN_STATS: &[u8] = b"tokenstats";
+/// prefix for the totals of each token per epoch
+pub const PREFIX_EPOCH_STATS: &[u8] = b"epochstats";
+/// prefix for the amount paid to each recipient per token per epoch
+pub const PREFIX_EPOCH_PAID: &[u8] = b"epochpaid";
+/// prefix for the list of recipients ever paid in each token
+pub const PREFIX_TOKEN_RECIPIENTS: &[u8] = b"tokenrecipients";
+/// prefix for named distributions that a Receive msg can select
+pub const PREFIX_NAMED_DISTRIBUTION: &[u8] = b"nameddistribution";
+/// prefix for timelocked changes to named distributions
+pub const PREFIX_PENDING_NAMED_DISTRIBUTION: &[u8] = b"pendingnameddistribution";
+/// storage key for the names of the named distributions, in creation order
+pub const DISTRIBUTION_NAMES_KEY: &[u8] = b"distributionnames";
+/// prefix for the splits anyone can create, keyed by split id
+pub const PREFIX_SPLITS: &[u8] = b"splits";
+/// storage key for the number of splits created
+pub const SPLIT_COUNT_KEY: &[u8] = b"splitcount";
+/// prefix for timelocked changes to splits
+pub const PREFIX_PENDING_SPLIT_DISTRIBUTION: &[u8] = b"pendingsplitdistribution";
+/// prefix for the ledger of the forwards of each split
+pub const PREFIX_SPLIT_LEDGER: &[u8] = b"splitledger";
+/// number of blocks in a stats epoch if none is given at init
+pub const DEFAULT_EPOCH_LENGTH: u64 = 100_000;
+/// number of forwarders funds can pass through before reaching this one if none is given
+/// at init
+pub const DEFAULT_MAX_FORWARD_DEPTH: u32 = 10;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
+    /// addresses allowed to perform or approve admin actions
+    pub admins: Vec<CanonicalAddr>,
+    /// number of admins that must approve an admin action
+    pub threshold: u16,
+    pub distribution_mode: DistributionMode,
+    /// this contract's address, needed to validate permits
+    pub contract_address: CanonicalAddr,
+    /// true if the public distribution query should hide recipient addresses
+    pub hide_recipients: bool,
+    /// address proposed as the new admin, which must accept before becoming admin
+    pub pending_admin: Option<CanonicalAddr>,
+    /// optional delay before distribution changes take effect
+    pub timelock: Option<Timelock>,
+    /// shorter timelock that takes effect once the current timelock has passed
+    pub pending_timelock: Option<PendingTimelock>,
+    /// true if lowering a recipient's rate requires that recipient's approval
+    pub locked_recipients: bool,
+    /// who besides the admins can register tokens
+    pub registration_policy: RegistrationPolicy,
+    /// number of blocks in each stats epoch
+    pub epoch_length: u64,
+    /// number of forwarders received funds can have passed through before reaching this one
+    pub max_forward_depth: u32,
+}
+
+/// how received snip20s reach their recipients
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DistributionMode {
+    /// every receive transfers each recipient its share
+    Push,
+    /// every receive credits each recipient's claimable balance, which they withdraw with Claim
+    Pull,
+}
+
+impl Default for DistributionMode {
+    fn default() -> Self {
+        DistributionMode::Push
+    }
+}
+
+/// who besides the admins can register tokens.  Only admins can give a token its own
+/// distribution or register a token again
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistrationPolicy {
+    /// only admins
+    AdminOnly,
+    /// anyone, for tokens with one of the listed code hashes
+    Allowlist { code_hashes: Vec<String> },
+    /// anyone, for any snip20
+    Open,
+}
+
+impl Default for RegistrationPolicy {
+    fn default() -> Self {
+        RegistrationPolicy::AdminOnly
+    }
+}
+
+impl RegistrationPolicy {
+    /// Returns bool, true if anyone may register a token with this code hash
+    ///
+    /// # Arguments
+    ///
+    /// * `code_hash` - the token's code hash
+    pub fn allows(&self, code_hash: &str) -> bool {
+        match self {
+            RegistrationPolicy::AdminOnly => false,
+            RegistrationPolicy::Allowlist { code_hashes } => {
+                code_hashes.iter().any(|h| h.eq_ignore_ascii_case(code_hash))
+            }
+            RegistrationPolicy::Open => true,
+        }
+    }
+}
+
+/// delay before a distribution change takes effect
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Timelock {
+    /// number of blocks
+    Blocks(u64),
+    /// number of seconds
+    Seconds(u64),
+}
+
+impl Timelock {
+    /// Returns Activation of when a change made in the current block will take effect
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - a reference to the current BlockInfo
+    pub fn activation(&self, block: &BlockInfo) -> Activation {
+        match self {
+            Timelock::Blocks(blocks) => Activation::Height(block.height.saturating_add(*blocks)),
+            Timelock::Seconds(seconds) => Activation::Time(block.time.saturating_add(*seconds)),
+        }
+    }
+
+    /// Returns bool, true if replacing the current timelock with the new one can not shorten
+    /// the delay
+    ///
+    /// # Arguments
+    ///
+    /// * `new` - a reference to the new timelock
+    /// * `current` - a reference to the current timelock
+    pub fn is_no_shorter(new: &Option<Timelock>, current: &Option<Timelock>) -> bool {
+        match (new, current) {
+            (_, None) => true,
+            (Some(Timelock::Blocks(n)), Some(Timelock::Blocks(c))) => n >= c,
+            (Some(Timelock::Seconds(n)), Some(Timelock::Seconds(c))) => n >= c,
+            _ => false,
+        }
+    }
+}
+
+/// when a timelocked change takes effect
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Activation {
+    /// block height
+    Height(u64),
+    /// block time in seconds since epoch
+    Time(u64),
+}
+
+impl Activation {
+    /// Returns bool, true if the change has taken effect by the current block
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - a reference to the current BlockInfo
+    pub fn is_reached(&self, block: &BlockInfo) -> bool {
+        match self {
+            Activation::Height(height) => block.height >= *height,
+            Activation::Time(time) => block.time >= *time,
+        }
+    }
+
+    /// Returns bool, true if the change has taken effect by the given height or time.  Queries
+    /// do not know the current block, so a value that is not given counts as not reached
+    ///
+    /// # Arguments
+    ///
+    /// * `height` - optional block height
+    /// * `time` - optional block time in seconds since epoch
+    pub fn is_reached_at(&self, height: Option<u64>, time: Option<u64>) -> bool {
+        match self {
+            Activation::Height(activation) => height.map_or(false, |h| h >= *activation),
+            Activation::Time(activation) => time.map_or(false, |t| t >= *activation),
+        }
+    }
+}
+
+/// a timelock change waiting for the current timelock to pass
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingTimelock {
+    pub timelock: Option<Timelock>,
+    pub activation: Activation,
+}
+
+/// a distribution change waiting for the timelock to pass
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StoredPendingDistribution {
+    /// the new distribution.  None removes a token's own distribution
+    pub dist_info: Option<StoredRoyaltyInfo>,
+    pub activation: Activation,
+}
+
+/// a registered snip20 contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StoredTokenInfo {
+    /// code hash of the snip20 contract
+    pub code_hash: String,
+    /// token name, as reported by the token when registered
+    pub name: String,
+    /// token symbol, as reported by the token when registered
+    pub symbol: String,
+    /// number of decimals in token amounts, as reported by the token when registered
+    pub decimals: u8,
+    /// block height when the token was registered
+    pub registered_height: u64,
+    /// false once the token is unregistered.  Its code hash is kept so claimable balances
+    /// can still be withdrawn
+    pub registered: bool,
+}
+
+/// identifies one of the contract's distributions
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum DistKey {
+    /// the default distribution
+    Default,
+    /// a token's own distribution
+    Token(HumanAddr),
+    /// a named distribution
+    Named(String),
+    /// a split's distribution
+    Split(u64),
+}
+
+/// an independent split with its own admin, which a Receive msg can select by id
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StoredSplit {
+    pub name: String,
+    /// address allowed to change the split
     pub admin: CanonicalAddr,
+    /// address proposed as the split's new admin, which must accept before becoming admin
+    pub pending_admin: Option<CanonicalAddr>,
+    pub dist_info: StoredRoyaltyInfo,
+    /// block height when the split was created
+    pub created_height: u64,
+}
+
+/// a distribution change awaiting the consent of the recipients whose rates it lowers
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StoredDistProposal {
+    /// distribution that is changed
+    pub key: DistKey,
+    /// the new distribution.  None removes a token's own distribution or a named one
+    pub dist_info: Option<StoredRoyaltyInfo>,
+    /// distribution in use when the change was proposed
+    pub base: Option<StoredRoyaltyInfo>,
+    /// recipients whose rates are lowered
+    pub required: Vec<CanonicalAddr>,
+    pub approvals: Vec<CanonicalAddr>,
+    pub executed: bool,
+    pub created_height: u64,
+}
+
+/// an admin action awaiting approval
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StoredProposal {
+    /// the action to perform once approved
+    pub action: AdminAction,
+    /// admins who approved the action
+    pub approvals: Vec<CanonicalAddr>,
+    /// true once the action has been performed
+    pub executed: bool,
+    /// block height when the proposal was created
+    pub created_height: u64,
+}
+
+/// a single payout to a recipient
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StoredPayoutRecord {
+    /// snip20 contract address or native denom
+    pub token: String,
+    /// amount paid
+    pub amount: u128,
+    /// block height of the payout
+    pub block_height: u64,
+    /// block time of the payout in seconds since epoch
+    pub block_time: u64,
+    /// id of the forward in the ledger
+    pub forward_id: u32,
+}
+
+/// a forward of received funds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StoredForward {
+    /// snip20 contract address or native denom
+    pub token: String,
+    /// address the funds came from
+    pub from: CanonicalAddr,
+    /// amount split among the recipients
+    pub amount: u128,
+    /// payouts to each recipient, including the dust
+    pub payouts: Vec<StoredPayout>,
+    /// amount left over by rounding that was added to one recipient's payout
+    pub dust: u128,
+    pub block_height: u64,
+    /// block time in seconds since epoch
+    pub block_time: u64,
 }
 
+/// running totals of the forwards of a token
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct StoredStats {
+    /// total amount received and split
+    pub received: u128,
+    /// total rounding dust added to payouts
+    pub dust: u128,
+    /// number of forwards
+    pub forwards: u32,
+}
 
+impl StoredStats {
+    /// adds a forward to the totals
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` - amount that was split
+    /// * `dust` - rounding dust added to a payout
+    pub fn add(&mut self, amount: u128, dust: u128) {
+        self.received = self.received.saturating_add(amount);
+        self.dust = self.dust.saturating_add(dust);
+        self.forwards = self.forwards.saturating_add(1);
+    }
+}
 
 
 
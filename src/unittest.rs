@@ -1,41 +1,135 @@
 #[cfg(test)]
 mod tests {
     use cosmwasm_std::{
-        to_binary, Api, Binary, CosmosMsg, Env, Extern, HandleResponse, HandleResult, HumanAddr, InitResponse, Uint128, Querier,
-        StdError, StdResult, Storage, CanonicalAddr, QueryResult, testing::mock_dependencies, testing::mock_env
+        to_binary, from_binary, Api, Binary, CosmosMsg, Env, Extern, HandleResponse, HandleResult, HumanAddr, InitResponse, Uint128, Querier,
+        StdError, StdResult, Storage, CanonicalAddr, QueryResult, WasmMsg, BankMsg, coin, testing::mock_env,
+        testing::{MockApi, MockStorage}, from_slice, Empty, QuerierResult, QueryRequest, WasmQuery, log
     };
+    use serde::{Deserialize, Serialize};
     use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
     use secret_toolkit::utils::InitCallback;
+    use secret_toolkit::permit::{Permit, PermitParams, PermitSignature, PubKey, TokenPermissions};
     
-    use crate::msg::{HandleMsg, InitMsg, QueryAnswer, QueryMsg};
-    use crate::state::{save, load, may_load, remove, Config, CONFIG_KEY, PREFIX_TOKEN_CONTRACT_INFO, FUNDS_DISTRIBUTION_KEY};
+    use crate::msg::{ForwardPayout, HandleAnswer, HandleMsg, HeightRange, InitMsg, Payout, QueryAnswer, QueryMsg, QueryWithPermit, ReceiveMsg, ResponseStatus, TokenBalance};
+    use crate::state::{save, load, may_load, remove, Activation, Config, DistributionMode, RegistrationPolicy, Timelock, CONFIG_KEY, PREFIX_TOKEN_CONTRACT_INFO, FUNDS_DISTRIBUTION_KEY};
     use crate::royalties::{RoyaltyInfo, StoredRoyaltyInfo, Royalty};
-    use crate::contract::{init, receive, register_token, forward_funds};
+    use crate::viewing_key::sha_256;
+    use crate::factory;
+    use crate::contract::{init, receive, register_token, forward_funds, handle, query, permit_query_as, BLOCK_SIZE, MAX_OPEN_REGISTRATIONS, MAX_QUERY_EPOCHS};
     
 
     
     use primitive_types::U256;
-    use secret_toolkit::{snip20::handle::{register_receive_msg,transfer_msg}};
+    use secret_toolkit::{snip20::handle::{register_receive_msg,send_msg,transfer_msg}};
 
     
 
 
 
+    #[derive(Serialize)]
+    struct TokenInfoResponse {
+        token_info: MockTokenInfo,
+    }
+
+    #[derive(Serialize)]
+    struct MockTokenInfo {
+        name: String,
+        symbol: String,
+        decimals: u8,
+        total_supply: Option<Uint128>,
+    }
+
+    #[derive(Serialize)]
+    struct BalanceResponse {
+        balance: MockBalance,
+    }
+
+    #[derive(Serialize)]
+    struct MockBalance {
+        amount: Uint128,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    enum Snip20Query {
+        TokenInfo {},
+        Balance {},
+    }
+
+    /// answers the token_info and balance queries of every snip20 except "not a token".
+    /// "stubborn" rejects balance queries
+    struct TokenQuerier {
+        /// balance reported for the contract
+        balance: u128,
+    }
+
+    impl Querier for TokenQuerier {
+        fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+            let request: QueryRequest<Empty> = from_slice(bin_request).unwrap();
+            match request {
+                QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg, .. }) => {
+                    if contract_addr == HumanAddr::from("not a token") {
+                        return Ok(Err(StdError::generic_err("unknown query")));
+                    }
+                    match from_binary::<Snip20Query>(&msg).unwrap() {
+                        Snip20Query::TokenInfo {} => {
+                            let token_info = MockTokenInfo {
+                                symbol: contract_addr.0.to_uppercase(),
+                                name: contract_addr.0,
+                                decimals: 6,
+                                total_supply: None,
+                            };
+                            Ok(to_binary(&TokenInfoResponse { token_info }))
+                        }
+                        Snip20Query::Balance {} if contract_addr == HumanAddr::from("stubborn") => {
+                            Ok(Err(StdError::generic_err("Wrong viewing key for this address or viewing key not set")))
+                        }
+                        Snip20Query::Balance {} => {
+                            let balance = MockBalance { amount: Uint128(self.balance) };
+                            Ok(to_binary(&BalanceResponse { balance }))
+                        }
+                    }
+                }
+                other => panic!("unexpected query {:?}", other),
+            }
+        }
+    }
+
+    fn mock_deps() -> Extern<MockStorage, MockApi, TokenQuerier> {
+        Extern {
+            storage: MockStorage::default(),
+            api: MockApi::new(20),
+            querier: TokenQuerier { balance: 0 },
+        }
+    }
+
     #[test]
 
     pub fn init_test() {
-        let mut deps = mock_dependencies(20, &[]);
+        let mut deps = mock_deps();
         let env = mock_env("addr1", &[]);
         
 
         let msg = InitMsg {
             admin: env.message.sender.clone(),
+            co_admins: vec![],
+            admin_threshold: None,
             dist_info: RoyaltyInfo {
                 decimal_places_in_rates: 1,
-                royalties: vec![ Royalty { recipient: env.message.sender.clone(), rate: 100 }],
+                royalties: vec![ Royalty { recipient: env.message.sender.clone(), rate: 100, code_hash: None, msg: None }],
+                remainder_recipient: None,
             },
             sscrt_addr: HumanAddr::from("Contract Address"),
             sscrt_hash: String::from("Snip20 hash"),
+            distribution_mode: DistributionMode::Push,
+            prng_seed: Binary::from(b"seed".to_vec()),
+            hide_recipients: false,
+            timelock: None,
+            locked_recipients: false,
+            registration_policy: RegistrationPolicy::AdminOnly,
+            stats_epoch_length: None,
+            max_forward_depth: None,
+            callback: None,
         };
 
         let receive_amount = Uint128(10);
@@ -44,14 +138,2025 @@ mod tests {
         init(&mut deps, env.clone(), msg);
 
         let env = mock_env("Contract Address", &[]);
-        forward_funds(&mut deps, env.clone(), receive_amount);
+        forward_funds(&mut deps, env.clone(), HumanAddr::from("donor"), receive_amount, ReceiveMsg::default());
 
         
-        register_token(&mut deps, env.clone(), HumanAddr::from("New Address"), String::from("New hash"));
+        register_token(&mut deps, env.clone(), HumanAddr::from("New Address"), String::from("New hash"), None);
 
         let env = mock_env("New Address", &[]);
-        forward_funds(&mut deps, env.clone(), receive_amount);
+        forward_funds(&mut deps, env.clone(), HumanAddr::from("donor"), receive_amount, ReceiveMsg::default());
+    }
+
+
+
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "snake_case")]
+    enum Snip20Msg {
+        Transfer {
+            recipient: HumanAddr,
+            amount: Uint128,
+        },
+    }
+
+    /// Returns the (recipient, amount) of every snip20 transfer in the messages
+    fn transfers(messages: &[CosmosMsg]) -> Vec<(HumanAddr, u128)> {
+        messages
+            .iter()
+            .map(|m| match m {
+                CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => {
+                    match from_binary::<Snip20Msg>(msg).unwrap() {
+                        Snip20Msg::Transfer { recipient, amount } => (recipient, amount.u128()),
+                    }
+                }
+                _ => panic!("unexpected message {:?}", m),
+            })
+            .collect()
+    }
+
+    fn three_way_split(remainder_recipient: Option<HumanAddr>) -> RoyaltyInfo {
+        RoyaltyInfo {
+            decimal_places_in_rates: 4,
+            royalties: vec![
+                Royalty { recipient: HumanAddr::from("alice"), rate: 3333, code_hash: None, msg: None },
+                Royalty { recipient: HumanAddr::from("bob"), rate: 3334, code_hash: None, msg: None },
+                Royalty { recipient: HumanAddr::from("carol"), rate: 3333, code_hash: None, msg: None },
+            ],
+            remainder_recipient,
+        }
+    }
+
+    fn init_with<S: Storage, A: Api, Q: Querier>(deps: &mut Extern<S, A, Q>, dist_info: RoyaltyInfo) {
+        let env = mock_env("admin", &[]);
+        let msg = InitMsg {
+            admin: env.message.sender.clone(),
+            co_admins: vec![],
+            admin_threshold: None,
+            dist_info,
+            sscrt_addr: HumanAddr::from("sscrt"),
+            sscrt_hash: String::from("sscrt hash"),
+            distribution_mode: DistributionMode::Push,
+            prng_seed: Binary::from(b"seed".to_vec()),
+            hide_recipients: false,
+            timelock: None,
+            locked_recipients: false,
+            registration_policy: RegistrationPolicy::AdminOnly,
+            stats_epoch_length: None,
+            max_forward_depth: None,
+            callback: None,
+        };
+        init(deps, env, msg).unwrap();
+    }
+
+    #[test]
+    pub fn split_conserves_awkward_amounts() {
+        let deps = mock_deps();
+        let stored = three_way_split(None).to_stored(&deps.api).unwrap();
+
+        for amount in [0u128, 1, 2, 7, 101, 9_999, 1_000_001, u64::MAX as u128, u128::MAX / 3].iter() {
+            let split = stored.split(*amount).unwrap();
+            let total: u128 = split.payouts.iter().map(|p| p.amount).sum();
+            assert_eq!(total, *amount);
+            assert!(split.dust < 3);
+        }
+
+        // without a remainder recipient, the largest share absorbs the dust
+        let split = stored.split(2).unwrap();
+        assert_eq!(split.dust, 2);
+        assert_eq!(split.payouts[1].amount, 2);
+    }
+
+    #[test]
+    pub fn forward_funds_routes_dust_to_remainder_recipient() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(Some(HumanAddr::from("carol"))));
+
+        let env = mock_env("sscrt", &[]);
+        let response = forward_funds(&mut deps, env, HumanAddr::from("donor"), Uint128(1_000_001), ReceiveMsg::default()).unwrap();
+        let sent = transfers(&response.messages);
+
+        assert_eq!(
+            sent,
+            vec![
+                (HumanAddr::from("alice"), 333_300),
+                (HumanAddr::from("bob"), 333_400),
+                (HumanAddr::from("carol"), 333_301),
+            ]
+        );
+        assert_eq!(sent.iter().map(|(_, a)| a).sum::<u128>(), 1_000_001);
+    }
+
+    #[test]
+    pub fn remainder_recipient_must_be_a_recipient() {
+        let mut deps = mock_deps();
+        let env = mock_env("admin", &[]);
+        let msg = InitMsg {
+            admin: env.message.sender.clone(),
+            co_admins: vec![],
+            admin_threshold: None,
+            dist_info: three_way_split(Some(HumanAddr::from("mallory"))),
+            sscrt_addr: HumanAddr::from("sscrt"),
+            sscrt_hash: String::from("sscrt hash"),
+            distribution_mode: DistributionMode::Push,
+            prng_seed: Binary::from(b"seed".to_vec()),
+            hide_recipients: false,
+            timelock: None,
+            locked_recipients: false,
+            registration_policy: RegistrationPolicy::AdminOnly,
+            stats_epoch_length: None,
+            max_forward_depth: None,
+            callback: None,
+        };
+
+        match init(&mut deps, env, msg) {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!(msg, "The remainder recipient must be one of the royalty recipients")
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn token_distribution_overrides_default() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(None));
+
+        let stable_dist = RoyaltyInfo {
+            decimal_places_in_rates: 0,
+            royalties: vec![Royalty { recipient: HumanAddr::from("dave"), rate: 1, code_hash: None, msg: None }],
+            remainder_recipient: None,
+        };
+        let msg = HandleMsg::RegisterToken {
+            snip20_addr: HumanAddr::from("stable"),
+            snip20_hash: String::from("stable hash"),
+            dist_info: Some(stable_dist.clone()),
+        };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+
+        let response = forward_funds(&mut deps, mock_env("stable", &[]), HumanAddr::from("donor"), Uint128(500), ReceiveMsg::default()).unwrap();
+        assert_eq!(transfers(&response.messages), vec![(HumanAddr::from("dave"), 500)]);
+
+        // sscrt still uses the default
+        let response = forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(500), ReceiveMsg::default()).unwrap();
+        assert_eq!(transfers(&response.messages).len(), 3);
+
+        let query_msg = QueryMsg::QueryDist { token: Some(HumanAddr::from("stable")), distribution: None, height: None, time: None };
+        match from_binary(&query(&deps, query_msg).unwrap()).unwrap() {
+            QueryAnswer::RoyaltyInfo { royalty_info, .. } => {
+                let dist = royalty_info.unwrap();
+                assert_eq!(dist.royalties[0].recipient, Some(HumanAddr::from("dave")));
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+
+        // removing the token distribution falls back to the default
+        let msg = HandleMsg::SetTokenDistribution {
+            snip20_addr: HumanAddr::from("stable"),
+            dist_info: None,
+        };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        let response = forward_funds(&mut deps, mock_env("stable", &[]), HumanAddr::from("donor"), Uint128(500), ReceiveMsg::default()).unwrap();
+        assert_eq!(transfers(&response.messages).len(), 3);
+
+        // only registered tokens can have a distribution
+        let msg = HandleMsg::SetTokenDistribution {
+            snip20_addr: HumanAddr::from("unknown"),
+            dist_info: Some(stable_dist),
+        };
+        assert!(handle(&mut deps, mock_env("admin", &[]), msg).is_err());
+    }
+
+    #[test]
+    pub fn forward_native_splits_each_denom() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(None));
+
+        let env = mock_env("donor", &[coin(1001, "uscrt"), coin(7, "uatom")]);
+        let response = handle(&mut deps, env, HandleMsg::ForwardNative { distribution: None, split_id: None }).unwrap();
+        let expected = vec![
+            ("alice", vec![coin(2, "uatom"), coin(333, "uscrt")]),
+            ("bob", vec![coin(3, "uatom"), coin(335, "uscrt")]),
+            ("carol", vec![coin(2, "uatom"), coin(333, "uscrt")]),
+        ];
+        assert_eq!(response.messages.len(), expected.len());
+        for (msg, (recipient, coins)) in response.messages.iter().zip(expected.into_iter()) {
+            assert_eq!(
+                msg,
+                &CosmosMsg::Bank(BankMsg::Send {
+                    from_address: HumanAddr::from("cosmos2contract"),
+                    to_address: HumanAddr::from(recipient),
+                    amount: coins,
+                })
+            );
+        }
+
+        // recipients whose share rounds down to nothing are skipped
+        let env = mock_env("donor", &[coin(2, "uscrt")]);
+        let response = handle(&mut deps, env, HandleMsg::ForwardNative { distribution: None, split_id: None }).unwrap();
+        assert_eq!(
+            response.messages,
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("bob"),
+                amount: vec![coin(2, "uscrt")],
+            })]
+        );
+
+        let env = mock_env("donor", &[]);
+        assert!(handle(&mut deps, env, HandleMsg::ForwardNative { distribution: None, split_id: None }).is_err());
+    }
+
+    #[test]
+    pub fn pull_mode_credits_and_claims() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(None));
+        let msg = HandleMsg::SetDistributionMode { mode: DistributionMode::Pull };
+        assert!(handle(&mut deps, mock_env("alice", &[]), msg.clone()).is_err());
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+
+        let response = forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(1_000), ReceiveMsg::default()).unwrap();
+        assert!(response.messages.is_empty());
+        forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(2), ReceiveMsg::default()).unwrap();
+
+        let msg = HandleMsg::SetViewingKey { key: String::from("bob key"), padding: None };
+        handle(&mut deps, mock_env("bob", &[]), msg).unwrap();
+        let query_msg = QueryMsg::QueryClaimable {
+            address: HumanAddr::from("bob"),
+            key: String::from("bob key"),
+            tokens: vec![HumanAddr::from("sscrt"), HumanAddr::from("stable")],
+        };
+        match from_binary(&query(&deps, query_msg).unwrap()).unwrap() {
+            QueryAnswer::Claimable { balances } => {
+                assert_eq!(balances[0].amount, Uint128(336));
+                assert_eq!(balances[1].amount, Uint128(0));
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+
+        let msg = HandleMsg::Claim { tokens: vec![HumanAddr::from("sscrt")] };
+        let response = handle(&mut deps, mock_env("bob", &[]), msg.clone()).unwrap();
+        assert_eq!(transfers(&response.messages), vec![(HumanAddr::from("bob"), 336)]);
+
+        // the balance was withdrawn
+        assert!(handle(&mut deps, mock_env("bob", &[]), msg).is_err());
+    }
+
+    #[test]
+    pub fn native_coins_are_rejected_in_pull_mode() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(None));
+        let msg = HandleMsg::SetDistributionMode { mode: DistributionMode::Pull };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+
+        let msg = HandleMsg::ForwardNative { distribution: None, split_id: None };
+        match handle(&mut deps, mock_env("donor", &[coin(10, "uscrt")]), msg.clone()) {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!(msg, "Native coins can not be forwarded in pull mode, because they can not be claimed");
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        // native coins sent along with snip20s are rejected too
+        let env = mock_env("sscrt", &[coin(10, "uscrt")]);
+        assert!(forward_funds(&mut deps, env, HumanAddr::from("donor"), Uint128(100), ReceiveMsg::default()).is_err());
+
+        // back in push mode they are forwarded
+        let mode_msg = HandleMsg::SetDistributionMode { mode: DistributionMode::Push };
+        handle(&mut deps, mock_env("admin", &[]), mode_msg).unwrap();
+        let response = handle(&mut deps, mock_env("donor", &[coin(10, "uscrt")]), msg).unwrap();
+        assert_eq!(response.messages.len(), 3);
+    }
+
+    #[test]
+    pub fn viewing_key_queries_show_own_earnings() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(None));
+        forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(1_000), ReceiveMsg::default()).unwrap();
+        handle(&mut deps, mock_env("donor", &[coin(10, "uscrt")]), HandleMsg::ForwardNative { distribution: None, split_id: None }).unwrap();
+        forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(10), ReceiveMsg::default()).unwrap();
+
+        let msg = HandleMsg::CreateViewingKey { entropy: String::from("entropy"), padding: None };
+        let response = handle(&mut deps, mock_env("alice", &[]), msg).unwrap();
+        let key = match from_binary(&response.data.unwrap()).unwrap() {
+            HandleAnswer::CreateViewingKey { key } => key.0,
+            other => panic!("unexpected answer {:?}", other),
+        };
+
+        // wrong key and someone else's address are rejected
+        let query_msg = QueryMsg::QueryEarnings {
+            address: HumanAddr::from("alice"),
+            key: String::from("wrong"),
+        };
+        assert!(query(&deps, query_msg).is_err());
+        let query_msg = QueryMsg::QueryEarnings {
+            address: HumanAddr::from("bob"),
+            key: key.clone(),
+        };
+        assert!(query(&deps, query_msg).is_err());
+
+        let query_msg = QueryMsg::QueryEarnings {
+            address: HumanAddr::from("alice"),
+            key: key.clone(),
+        };
+        match from_binary(&query(&deps, query_msg).unwrap()).unwrap() {
+            QueryAnswer::Earnings { earnings } => {
+                assert_eq!(earnings.len(), 2);
+                assert_eq!(earnings[0].token, "sscrt");
+                assert_eq!(earnings[0].amount, Uint128(336));
+                assert_eq!(earnings[1].token, "uscrt");
+                assert_eq!(earnings[1].amount, Uint128(3));
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+
+        let query_msg = QueryMsg::QueryPayouts {
+            address: HumanAddr::from("alice"),
+            key: key.clone(),
+            token: None,
+            page: None,
+            page_size: 2,
+        };
+        match from_binary(&query(&deps, query_msg).unwrap()).unwrap() {
+            QueryAnswer::Payouts { payouts, total } => {
+                assert_eq!(total, 3);
+                assert_eq!(payouts.len(), 2);
+                assert_eq!(payouts[0].token, "sscrt");
+                assert_eq!(payouts[0].amount, Uint128(3));
+                assert_eq!(payouts[1].token, "uscrt");
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+
+        let query_msg = QueryMsg::QueryShare {
+            address: HumanAddr::from("alice"),
+            key,
+            token: None,
+            distribution: None,
+            height: None,
+            time: None,
+        };
+        match from_binary(&query(&deps, query_msg).unwrap()).unwrap() {
+            QueryAnswer::Share { rate, decimal_places_in_rates, .. } => {
+                assert_eq!(rate, Uint128(3333));
+                assert_eq!(decimal_places_in_rates, 4);
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn hidden_recipients_are_only_shown_to_admin_and_themselves() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(Some(HumanAddr::from("bob"))));
+        let msg = HandleMsg::SetHideRecipients { hide_recipients: true };
+        assert!(handle(&mut deps, mock_env("bob", &[]), msg.clone()).is_err());
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+
+        let recipients = |answer: QueryAnswer| match answer {
+            QueryAnswer::RoyaltyInfo { royalty_info, .. } => {
+                let dist = royalty_info.unwrap();
+                let mut shown: Vec<Option<HumanAddr>> =
+                    dist.royalties.into_iter().map(|r| r.recipient).collect();
+                shown.push(dist.remainder_recipient);
+                shown
+            }
+            other => panic!("unexpected answer {:?}", other),
+        };
+
+        let public = query(&deps, QueryMsg::QueryDist { token: None, distribution: None, height: None, time: None }).unwrap();
+        assert_eq!(recipients(from_binary(&public).unwrap()), vec![None, None, None, None]);
+
+        for viewer in ["admin", "bob"].iter() {
+            let msg = HandleMsg::SetViewingKey { key: String::from("key"), padding: None };
+            handle(&mut deps, mock_env(*viewer, &[]), msg).unwrap();
+        }
+        let query_msg = QueryMsg::QueryPrivateDist {
+            address: HumanAddr::from("admin"),
+            key: String::from("key"),
+            token: None,
+            distribution: None,
+            height: None,
+            time: None,
+        };
+        let admin_view = query(&deps, query_msg).unwrap();
+        assert_eq!(
+            recipients(from_binary(&admin_view).unwrap()),
+            vec![
+                Some(HumanAddr::from("alice")),
+                Some(HumanAddr::from("bob")),
+                Some(HumanAddr::from("carol")),
+                Some(HumanAddr::from("bob")),
+            ]
+        );
+
+        let query_msg = QueryMsg::QueryPrivateDist {
+            address: HumanAddr::from("bob"),
+            key: String::from("key"),
+            token: None,
+            distribution: None,
+            height: None,
+            time: None,
+        };
+        let bob_view = query(&deps, query_msg).unwrap();
+        assert_eq!(
+            recipients(from_binary(&bob_view).unwrap()),
+            vec![None, Some(HumanAddr::from("bob")), None, Some(HumanAddr::from("bob"))]
+        );
+    }
+
+    #[test]
+    pub fn admin_transfer_requires_acceptance() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(None));
+
+        let msg = HandleMsg::ProposeAdmin { admin_addr: HumanAddr::from("new admin") };
+        assert!(handle(&mut deps, mock_env("alice", &[]), msg.clone()).is_err());
+        handle(&mut deps, mock_env("admin", &[]), msg.clone()).unwrap();
+
+        match from_binary(&query(&deps, QueryMsg::QueryConfig {}).unwrap()).unwrap() {
+            QueryAnswer::Config { admins, pending_admin, .. } => {
+                assert_eq!(admins, vec![HumanAddr::from("admin")]);
+                assert_eq!(pending_admin, Some(HumanAddr::from("new admin")));
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+
+        // cancelled proposals can not be accepted
+        handle(&mut deps, mock_env("admin", &[]), HandleMsg::CancelAdminProposal {}).unwrap();
+        assert!(handle(&mut deps, mock_env("new admin", &[]), HandleMsg::AcceptAdmin {}).is_err());
+
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        assert!(handle(&mut deps, mock_env("alice", &[]), HandleMsg::AcceptAdmin {}).is_err());
+        handle(&mut deps, mock_env("new admin", &[]), HandleMsg::AcceptAdmin {}).unwrap();
+
+        match from_binary(&query(&deps, QueryMsg::QueryConfig {}).unwrap()).unwrap() {
+            QueryAnswer::Config { admins, pending_admin, .. } => {
+                assert_eq!(admins, vec![HumanAddr::from("new admin")]);
+                assert_eq!(pending_admin, None);
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+        let msg = HandleMsg::SetHideRecipients { hide_recipients: true };
+        assert!(handle(&mut deps, mock_env("admin", &[]), msg.clone()).is_err());
+        handle(&mut deps, mock_env("new admin", &[]), msg).unwrap();
+    }
+
+    #[test]
+    pub fn multisig_admin_actions_need_threshold_approvals() {
+        let mut deps = mock_deps();
+        let env = mock_env("admin", &[]);
+        let msg = InitMsg {
+            admin: env.message.sender.clone(),
+            co_admins: vec![HumanAddr::from("co admin 1"), HumanAddr::from("co admin 2")],
+            admin_threshold: Some(2),
+            dist_info: three_way_split(None),
+            sscrt_addr: HumanAddr::from("sscrt"),
+            sscrt_hash: String::from("sscrt hash"),
+            distribution_mode: DistributionMode::Push,
+            prng_seed: Binary::from(b"seed".to_vec()),
+            hide_recipients: false,
+            timelock: None,
+            locked_recipients: false,
+            registration_policy: RegistrationPolicy::AdminOnly,
+            stats_epoch_length: None,
+            max_forward_depth: None,
+            callback: None,
+        };
+        init(&mut deps, env, msg).unwrap();
+
+        let msg = HandleMsg::ChangeDistribution {
+            dist_info: RoyaltyInfo {
+                decimal_places_in_rates: 2,
+                royalties: vec![Royalty { recipient: HumanAddr::from("dave"), rate: 100, code_hash: None, msg: None }],
+                remainder_recipient: None,
+            },
+        };
+        assert!(handle(&mut deps, mock_env("alice", &[]), msg.clone()).is_err());
+        let response = handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        let proposal_id = match from_binary(&response.data.unwrap()).unwrap() {
+            HandleAnswer::Proposal { proposal_id, approvals } => {
+                assert_eq!(approvals, 1);
+                proposal_id
+            }
+            other => panic!("unexpected answer {:?}", other),
+        };
+
+        // nothing changes until enough admins approve
+        let response = forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(100), ReceiveMsg::default()).unwrap();
+        assert_eq!(transfers(&response.messages).len(), 3);
+
+        let msg = HandleMsg::SetViewingKey { key: String::from("key"), padding: None };
+        handle(&mut deps, mock_env("co admin 2", &[]), msg).unwrap();
+        let query_msg = QueryMsg::QueryProposals {
+            address: HumanAddr::from("co admin 2"),
+            key: String::from("key"),
+            page: None,
+            page_size: 10,
+        };
+        match from_binary(&query(&deps, query_msg.clone()).unwrap()).unwrap() {
+            QueryAnswer::Proposals { proposals, total } => {
+                assert_eq!(total, 1);
+                assert_eq!(proposals.len(), 1);
+                assert_eq!(proposals[0].approvals, vec![HumanAddr::from("admin")]);
+                assert!(!proposals[0].executed);
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+
+        let approve = HandleMsg::ApproveProposal { proposal_id };
+        assert!(handle(&mut deps, mock_env("alice", &[]), approve.clone()).is_err());
+        assert!(handle(&mut deps, mock_env("admin", &[]), approve.clone()).is_err());
+        handle(&mut deps, mock_env("co admin 1", &[]), approve.clone()).unwrap();
+
+        let response = forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(100), ReceiveMsg::default()).unwrap();
+        assert_eq!(transfers(&response.messages), vec![(HumanAddr::from("dave"), 100)]);
+        assert!(handle(&mut deps, mock_env("co admin 2", &[]), approve).is_err());
+        match from_binary(&query(&deps, query_msg).unwrap()).unwrap() {
+            QueryAnswer::Proposals { proposals, .. } => assert!(proposals[0].executed),
+            other => panic!("unexpected answer {:?}", other),
+        }
+
+        // the threshold can not exceed the number of admins
+        let msg = HandleMsg::ChangeAdmins {
+            admins: vec![HumanAddr::from("admin")],
+            threshold: 2,
+        };
+        let response = handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        let proposal_id = match from_binary(&response.data.unwrap()).unwrap() {
+            HandleAnswer::Proposal { proposal_id, .. } => proposal_id,
+            other => panic!("unexpected answer {:?}", other),
+        };
+        let approve = HandleMsg::ApproveProposal { proposal_id };
+        assert!(handle(&mut deps, mock_env("co admin 1", &[]), approve).is_err());
+
+        // proposals are paged most recent first
+        let page_query = |page| QueryMsg::QueryProposals {
+            address: HumanAddr::from("co admin 2"),
+            key: String::from("key"),
+            page: Some(page),
+            page_size: 1,
+        };
+        for (page, expected_id) in vec![(0, proposal_id), (1, proposal_id - 1)] {
+            match from_binary(&query(&deps, page_query(page)).unwrap()).unwrap() {
+                QueryAnswer::Proposals { proposals, total } => {
+                    assert_eq!(total, 2);
+                    assert_eq!(proposals.len(), 1);
+                    assert_eq!(proposals[0].proposal_id, expected_id);
+                }
+                other => panic!("unexpected answer {:?}", other),
+            }
+        }
+        match from_binary(&query(&deps, page_query(2)).unwrap()).unwrap() {
+            QueryAnswer::Proposals { proposals, .. } => assert!(proposals.is_empty()),
+            other => panic!("unexpected answer {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn timelocked_distribution_changes_are_public_before_taking_effect() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(None));
+        let msg = HandleMsg::SetTimelock { timelock: Some(Timelock::Blocks(100)) };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+
+        let dave_only = RoyaltyInfo {
+            decimal_places_in_rates: 0,
+            royalties: vec![Royalty { recipient: HumanAddr::from("dave"), rate: 1, code_hash: None, msg: None }],
+            remainder_recipient: None,
+        };
+        let msg = HandleMsg::ChangeDistribution { dist_info: dave_only.clone() };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+
+        // the change is shown but the old distribution is still used
+        let query_msg = QueryMsg::QueryDist { token: Some(HumanAddr::from("sscrt")), distribution: None, height: None, time: None };
+        match from_binary(&query(&deps, query_msg).unwrap()).unwrap() {
+            QueryAnswer::RoyaltyInfo { royalty_info, pending } => {
+                assert_eq!(royalty_info.unwrap().royalties.len(), 3);
+                let pending = pending.unwrap();
+                assert_eq!(pending.activation, Activation::Height(12_445));
+                assert_eq!(
+                    pending.royalty_info.unwrap().royalties[0].recipient,
+                    Some(HumanAddr::from("dave"))
+                );
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+        let response = forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(500), ReceiveMsg::default()).unwrap();
+        assert_eq!(transfers(&response.messages).len(), 3);
+
+        // once the timelock passes, the new distribution is used
+        let mut env = mock_env("sscrt", &[]);
+        env.block.height += 100;
+        let response = forward_funds(&mut deps, env, HumanAddr::from("donor"), Uint128(500), ReceiveMsg::default()).unwrap();
+        assert_eq!(transfers(&response.messages), vec![(HumanAddr::from("dave"), 500)]);
+        match from_binary(&query(&deps, QueryMsg::QueryDist { token: None, distribution: None, height: None, time: None }).unwrap()).unwrap() {
+            QueryAnswer::RoyaltyInfo { pending, .. } => assert!(pending.is_none()),
+            other => panic!("unexpected answer {:?}", other),
+        }
+
+        // a pending change can be cancelled, but only by the admin
+        let msg = HandleMsg::ChangeDistribution { dist_info: three_way_split(None) };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        let cancel = HandleMsg::CancelDistributionChange { token: None, distribution: None };
+        assert!(handle(&mut deps, mock_env("alice", &[]), cancel.clone()).is_err());
+        handle(&mut deps, mock_env("admin", &[]), cancel.clone()).unwrap();
+        assert!(handle(&mut deps, mock_env("admin", &[]), cancel).is_err());
+        let mut env = mock_env("sscrt", &[]);
+        env.block.height += 200;
+        let response = forward_funds(&mut deps, env, HumanAddr::from("donor"), Uint128(500), ReceiveMsg::default()).unwrap();
+        assert_eq!(transfers(&response.messages), vec![(HumanAddr::from("dave"), 500)]);
+
+        // shortening the timelock waits out the current one
+        let msg = HandleMsg::SetTimelock { timelock: None };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        match from_binary(&query(&deps, QueryMsg::QueryConfig {}).unwrap()).unwrap() {
+            QueryAnswer::Config { timelock, pending_timelock, .. } => {
+                assert_eq!(timelock, Some(Timelock::Blocks(100)));
+                assert_eq!(pending_timelock.unwrap().activation, Activation::Height(12_445));
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+        let mut env = mock_env("admin", &[]);
+        env.block.height += 100;
+        let msg = HandleMsg::ChangeDistribution { dist_info: three_way_split(None) };
+        handle(&mut deps, env.clone(), msg).unwrap();
+        env.message.sender = HumanAddr::from("sscrt");
+        let response = forward_funds(&mut deps, env, HumanAddr::from("donor"), Uint128(500), ReceiveMsg::default()).unwrap();
+        assert_eq!(transfers(&response.messages).len(), 3);
+    }
+
+    #[test]
+    pub fn matured_distribution_changes_are_shown_in_use_before_a_forward() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(None));
+        let msg = HandleMsg::SetTimelock { timelock: Some(Timelock::Blocks(100)) };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        let dave_only = RoyaltyInfo {
+            decimal_places_in_rates: 0,
+            royalties: vec![Royalty { recipient: HumanAddr::from("dave"), rate: 1, code_hash: None, msg: None }],
+            remainder_recipient: None,
+        };
+        let msg = HandleMsg::ChangeDistribution { dist_info: dave_only };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+
+        // before the timelock passes, the change is still pending
+        let query_msg = QueryMsg::QueryDist {
+            token: Some(HumanAddr::from("sscrt")),
+            distribution: None,
+            height: Some(12_444),
+            time: None,
+        };
+        match from_binary(&query(&deps, query_msg).unwrap()).unwrap() {
+            QueryAnswer::RoyaltyInfo { royalty_info, pending } => {
+                assert_eq!(royalty_info.unwrap().royalties.len(), 3);
+                assert!(pending.is_some());
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+
+        // once it passes, the change is shown in use though no forward has applied it
+        let query_msg = QueryMsg::QueryDist {
+            token: Some(HumanAddr::from("sscrt")),
+            distribution: None,
+            height: Some(12_445),
+            time: None,
+        };
+        match from_binary(&query(&deps, query_msg).unwrap()).unwrap() {
+            QueryAnswer::RoyaltyInfo { royalty_info, pending } => {
+                let royalties = royalty_info.unwrap().royalties;
+                assert_eq!(royalties.len(), 1);
+                assert_eq!(royalties[0].recipient, Some(HumanAddr::from("dave")));
+                assert!(pending.is_none());
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+        let msg = HandleMsg::SetViewingKey { key: String::from("key"), padding: None };
+        handle(&mut deps, mock_env("dave", &[]), msg).unwrap();
+        let query_msg = QueryMsg::QueryShare {
+            address: HumanAddr::from("dave"),
+            key: String::from("key"),
+            token: None,
+            distribution: None,
+            height: Some(12_445),
+            time: None,
+        };
+        match from_binary(&query(&deps, query_msg).unwrap()).unwrap() {
+            QueryAnswer::Share { rate, .. } => assert_eq!(rate, Uint128(1)),
+            other => panic!("unexpected answer {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn permit_queries_show_matured_distribution_changes() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(None));
+        let msg = HandleMsg::SetTimelock { timelock: Some(Timelock::Blocks(100)) };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        let dave_only = RoyaltyInfo {
+            decimal_places_in_rates: 0,
+            royalties: vec![Royalty { recipient: HumanAddr::from("dave"), rate: 1, code_hash: None, msg: None }],
+            remainder_recipient: None,
+        };
+        let msg = HandleMsg::ChangeDistribution { dist_info: dave_only };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+
+        let permit = Permit {
+            params: PermitParams {
+                allowed_tokens: vec![HumanAddr::from("cosmos2contract")],
+                permit_name: String::from("dave's permit"),
+                chain_id: String::from("secret-4"),
+                permissions: vec![TokenPermissions::Balance],
+            },
+            signature: PermitSignature {
+                pub_key: PubKey {
+                    r#type: String::from("tendermint/PubKeySecp256k1"),
+                    value: Binary::from(vec![2u8; 33]),
+                },
+                signature: Binary::from(vec![0u8; 64]),
+            },
+        };
+        let dave = HumanAddr::from("dave");
+
+        // before the timelock passes, dave has no share
+        let query = QueryWithPermit::Share { token: None, distribution: None, height: Some(12_444), time: None };
+        match from_binary(&permit_query_as(&deps, &permit, &dave, query).unwrap()).unwrap() {
+            QueryAnswer::Share { rate, .. } => assert_eq!(rate, Uint128(0)),
+            other => panic!("unexpected answer {:?}", other),
+        }
+
+        // once it passes, the matured change is used without a forward applying it
+        let query = QueryWithPermit::Share { token: None, distribution: None, height: Some(12_445), time: None };
+        match from_binary(&permit_query_as(&deps, &permit, &dave, query).unwrap()).unwrap() {
+            QueryAnswer::Share { rate, .. } => assert_eq!(rate, Uint128(1)),
+            other => panic!("unexpected answer {:?}", other),
+        }
+        let query = QueryWithPermit::PrivateDist { token: None, distribution: None, height: Some(12_445), time: None };
+        match from_binary(&permit_query_as(&deps, &permit, &dave, query).unwrap()).unwrap() {
+            QueryAnswer::RoyaltyInfo { royalty_info, pending } => {
+                let royalties = royalty_info.unwrap().royalties;
+                assert_eq!(royalties.len(), 1);
+                assert_eq!(royalties[0].recipient, Some(dave.clone()));
+                assert!(pending.is_none());
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn locked_recipients_must_approve_lowered_rates() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(None));
+        handle(&mut deps, mock_env("admin", &[]), HandleMsg::LockRecipients {}).unwrap();
+
+        // removing carol needs her approval
+        let halves = |decimal_places_in_rates: u8, rate: u16| RoyaltyInfo {
+            decimal_places_in_rates,
+            royalties: vec![
+                Royalty { recipient: HumanAddr::from("alice"), rate, code_hash: None, msg: None },
+                Royalty { recipient: HumanAddr::from("bob"), rate, code_hash: None, msg: None },
+            ],
+            remainder_recipient: None,
+        };
+        let msg = HandleMsg::ChangeDistribution { dist_info: halves(4, 5000) };
+        let response = handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        let proposal_id = match from_binary(&response.data.unwrap()).unwrap() {
+            HandleAnswer::DistributionProposal { proposal_id, awaiting } => {
+                assert_eq!(awaiting, 1);
+                proposal_id
+            }
+            other => panic!("unexpected answer {:?}", other),
+        };
+        let response = forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(500), ReceiveMsg::default()).unwrap();
+        assert_eq!(transfers(&response.messages).len(), 3);
+
+        let msg = HandleMsg::SetViewingKey { key: String::from("key"), padding: None };
+        handle(&mut deps, mock_env("carol", &[]), msg).unwrap();
+        let query_msg = QueryMsg::QueryDistributionProposal {
+            address: HumanAddr::from("carol"),
+            key: String::from("key"),
+            proposal_id,
+        };
+        match from_binary(&query(&deps, query_msg).unwrap()).unwrap() {
+            QueryAnswer::DistributionProposal { proposal } => {
+                assert_eq!(proposal.required, vec![HumanAddr::from("carol")]);
+                assert!(proposal.approvals.is_empty());
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+
+        // only the lowered recipient can approve
+        let approve = HandleMsg::ApproveDistributionChange { proposal_id };
+        assert!(handle(&mut deps, mock_env("alice", &[]), approve.clone()).is_err());
+        assert!(handle(&mut deps, mock_env("admin", &[]), approve.clone()).is_err());
+        handle(&mut deps, mock_env("carol", &[]), approve.clone()).unwrap();
+        assert!(handle(&mut deps, mock_env("carol", &[]), approve).is_err());
+        let response = forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(500), ReceiveMsg::default()).unwrap();
+        assert_eq!(
+            transfers(&response.messages),
+            vec![(HumanAddr::from("alice"), 250), (HumanAddr::from("bob"), 250)]
+        );
+
+        // the same rates at more decimal places lower no one
+        let msg = HandleMsg::ChangeDistribution { dist_info: halves(5, 50000) };
+        let response = handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        match from_binary(&response.data.unwrap()).unwrap() {
+            HandleAnswer::ChangeDistribution { status } => assert_eq!(status, ResponseStatus::Success),
+            other => panic!("unexpected answer {:?}", other),
+        }
+        match from_binary(&query(&deps, QueryMsg::QueryConfig {}).unwrap()).unwrap() {
+            QueryAnswer::Config { locked_recipients, .. } => assert!(locked_recipients),
+            other => panic!("unexpected answer {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn recipients_can_move_their_own_share() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(Some(HumanAddr::from("alice"))));
+        let msg = HandleMsg::RegisterToken {
+            snip20_addr: HumanAddr::from("stable"),
+            snip20_hash: String::from("stable hash"),
+            dist_info: Some(three_way_split(None)),
+        };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+
+        let msg = HandleMsg::ChangeMyRecipientAddress {
+            new_addr: HumanAddr::from("alice2"),
+            require_acceptance: false,
+        };
+        assert!(handle(&mut deps, mock_env("dave", &[]), msg.clone()).is_err());
+        let response = handle(&mut deps, mock_env("alice", &[]), msg).unwrap();
+        assert_eq!(response.log[0], log("action", "change_my_recipient_address"));
+        assert_eq!(response.log[2], log("new_address", "alice2"));
+
+        // the rate and the remainder both move, in every distribution
+        for token in ["sscrt", "stable"].iter() {
+            let response = forward_funds(&mut deps, mock_env(*token, &[]), HumanAddr::from("donor"), Uint128(100), ReceiveMsg::default()).unwrap();
+            assert_eq!(
+                transfers(&response.messages),
+                vec![
+                    (HumanAddr::from("alice2"), if *token == "sscrt" { 34 } else { 33 }),
+                    (HumanAddr::from("bob"), if *token == "sscrt" { 33 } else { 34 }),
+                    (HumanAddr::from("carol"), 33),
+                ]
+            );
+        }
+
+        // a move needing acceptance waits for the new address
+        let msg = HandleMsg::ChangeMyRecipientAddress {
+            new_addr: HumanAddr::from("bob2"),
+            require_acceptance: true,
+        };
+        handle(&mut deps, mock_env("bob", &[]), msg).unwrap();
+        let response = forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(100), ReceiveMsg::default()).unwrap();
+        assert_eq!(transfers(&response.messages)[1].0, HumanAddr::from("bob"));
+        let accept = HandleMsg::AcceptRecipientAddress { old_addr: HumanAddr::from("bob") };
+        assert!(handle(&mut deps, mock_env("carol", &[]), accept.clone()).is_err());
+        handle(&mut deps, mock_env("bob2", &[]), accept.clone()).unwrap();
+        assert!(handle(&mut deps, mock_env("bob2", &[]), accept).is_err());
+        let response = forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(100), ReceiveMsg::default()).unwrap();
+        assert_eq!(transfers(&response.messages)[1].0, HumanAddr::from("bob2"));
+    }
+
+    #[test]
+    pub fn tokens_can_be_listed_updated_and_unregistered() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(None));
+        let register = |hash: &str| HandleMsg::RegisterToken {
+            snip20_addr: HumanAddr::from("stable"),
+            snip20_hash: String::from(hash),
+            dist_info: None,
+        };
+        let mut env = mock_env("admin", &[]);
+        env.block.height = 20_000;
+        handle(&mut deps, env.clone(), register("wrong hash")).unwrap();
+
+        // registering again updates the hash but keeps the registration height
+        env.block.height = 30_000;
+        handle(&mut deps, env, register("stable hash")).unwrap();
+        let query_msg = QueryMsg::QueryRegisteredTokens { page: Some(1), page_size: 1 };
+        match from_binary(&query(&deps, query_msg).unwrap()).unwrap() {
+            QueryAnswer::RegisteredTokens { tokens, total } => {
+                assert_eq!(total, 2);
+                assert_eq!(tokens.len(), 1);
+                assert_eq!(tokens[0].address, HumanAddr::from("stable"));
+                assert_eq!(tokens[0].code_hash, String::from("stable hash"));
+                assert_eq!(tokens[0].registered_height, 20_000);
+                assert_eq!(tokens[0].symbol, String::from("STABLE"));
+                assert_eq!(tokens[0].decimals, 6);
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+
+        // only snip20 contracts can be registered
+        let msg = HandleMsg::RegisterToken {
+            snip20_addr: HumanAddr::from("not a token"),
+            snip20_hash: String::from("hash"),
+            dist_info: None,
+        };
+        assert!(handle(&mut deps, mock_env("admin", &[]), msg).is_err());
+
+        let msg = HandleMsg::UnregisterToken { snip20_addr: HumanAddr::from("stable") };
+        assert!(handle(&mut deps, mock_env("alice", &[]), msg.clone()).is_err());
+        handle(&mut deps, mock_env("admin", &[]), msg.clone()).unwrap();
+        assert!(handle(&mut deps, mock_env("admin", &[]), msg).is_err());
+        assert!(forward_funds(&mut deps, mock_env("stable", &[]), HumanAddr::from("donor"), Uint128(100), ReceiveMsg::default()).is_err());
+        let query_msg = QueryMsg::QueryRegisteredTokens { page: None, page_size: 10 };
+        match from_binary(&query(&deps, query_msg).unwrap()).unwrap() {
+            QueryAnswer::RegisteredTokens { tokens, total } => {
+                assert_eq!(total, 1);
+                assert_eq!(tokens[0].address, HumanAddr::from("sscrt"));
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn registration_policy_lets_anyone_register_approved_tokens() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(None));
+        let register = |addr: &str, hash: &str| HandleMsg::RegisterToken {
+            snip20_addr: HumanAddr::from(addr),
+            snip20_hash: String::from(hash),
+            dist_info: None,
+        };
+        assert!(handle(&mut deps, mock_env("donor", &[]), register("stable", "snip20 hash")).is_err());
+
+        let msg = HandleMsg::SetRegistrationPolicy {
+            policy: RegistrationPolicy::Allowlist { code_hashes: vec![String::from("SNIP20 HASH")] },
+        };
+        assert!(handle(&mut deps, mock_env("donor", &[]), msg.clone()).is_err());
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+
+        assert!(handle(&mut deps, mock_env("donor", &[]), register("stable", "other hash")).is_err());
+        handle(&mut deps, mock_env("donor", &[]), register("stable", "snip20 hash")).unwrap();
+        forward_funds(&mut deps, mock_env("stable", &[]), HumanAddr::from("donor"), Uint128(100), ReceiveMsg::default()).unwrap();
+
+        // only the admin can update a registered token or give it its own distribution
+        assert!(handle(&mut deps, mock_env("donor", &[]), register("stable", "snip20 hash")).is_err());
+        let msg = HandleMsg::RegisterToken {
+            snip20_addr: HumanAddr::from("usdc"),
+            snip20_hash: String::from("snip20 hash"),
+            dist_info: Some(three_way_split(None)),
+        };
+        assert!(handle(&mut deps, mock_env("donor", &[]), msg.clone()).is_err());
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+
+        let msg = HandleMsg::SetRegistrationPolicy { policy: RegistrationPolicy::Open };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        handle(&mut deps, mock_env("donor", &[]), register("atom", "any hash")).unwrap();
+        assert!(handle(&mut deps, mock_env("donor", &[]), register("not a token", "any hash")).is_err());
+
+        // anyone can only grow the registry so far
+        let query_msg = QueryMsg::QueryRegisteredTokens { page: None, page_size: 1 };
+        let mut count = match from_binary(&query(&deps, query_msg).unwrap()).unwrap() {
+            QueryAnswer::RegisteredTokens { total, .. } => total as usize,
+            other => panic!("unexpected answer {:?}", other),
+        };
+        while count < MAX_OPEN_REGISTRATIONS {
+            handle(&mut deps, mock_env("donor", &[]), register(&format!("token {}", count), "any hash")).unwrap();
+            count += 1;
+        }
+        assert!(handle(&mut deps, mock_env("donor", &[]), register("one more", "any hash")).is_err());
+        handle(&mut deps, mock_env("admin", &[]), register("one more", "any hash")).unwrap();
+    }
+
+    #[test]
+    pub fn sweeps_and_redistributions_leave_claimable_funds() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(None));
+        let msg = HandleMsg::SetDistributionMode { mode: DistributionMode::Pull };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(1_000), ReceiveMsg::default()).unwrap();
+        // 500 more was sent with transfer instead of send
+        deps.querier.balance = 1_500;
+
+        let sweep = |amount: u128| HandleMsg::Sweep {
+            token: HumanAddr::from("sscrt"),
+            amount: Uint128(amount),
+            recipient: HumanAddr::from("treasury"),
+        };
+        // the contract set its viewing key when sscrt was registered, and the admin can replace it
+        let msg = HandleMsg::SetTokenViewingKey {
+            token: HumanAddr::from("sscrt"),
+            key: String::from("contract key"),
+        };
+        let response = handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        assert_eq!(response.messages.len(), 1);
+
+        assert!(handle(&mut deps, mock_env("alice", &[]), sweep(500)).is_err());
+        assert!(handle(&mut deps, mock_env("admin", &[]), sweep(501)).is_err());
+        let response = handle(&mut deps, mock_env("admin", &[]), sweep(500)).unwrap();
+        assert_eq!(transfers(&response.messages), vec![(HumanAddr::from("treasury"), 500)]);
+
+        // once alice claims, only bob's and carol's shares are owed
+        handle(&mut deps, mock_env("alice", &[]), HandleMsg::Claim { tokens: vec![HumanAddr::from("sscrt")] }).unwrap();
+        deps.querier.balance = 1_000;
+        let msg = HandleMsg::SetDistributionMode { mode: DistributionMode::Push };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        let msg = HandleMsg::RedistributeBalance { token: HumanAddr::from("sscrt") };
+        let response = handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        let total: u128 = transfers(&response.messages).iter().map(|(_, amount)| amount).sum();
+        assert_eq!(total, 333);
+
+        let msg = HandleMsg::SweepNative {
+            amount: vec![coin(40, "uscrt")],
+            recipient: HumanAddr::from("treasury"),
+        };
+        let response = handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        match &response.messages[0] {
+            CosmosMsg::Bank(BankMsg::Send { to_address, amount, .. }) => {
+                assert_eq!(to_address, &HumanAddr::from("treasury"));
+                assert_eq!(amount, &vec![coin(40, "uscrt")]);
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn registering_sets_the_contract_viewing_key() {
+        let mut deps = mock_deps();
+        let env = mock_env("admin", &[]);
+        let msg = InitMsg {
+            admin: env.message.sender.clone(),
+            co_admins: vec![],
+            admin_threshold: None,
+            dist_info: three_way_split(None),
+            sscrt_addr: HumanAddr::from("sscrt"),
+            sscrt_hash: String::from("sscrt hash"),
+            distribution_mode: DistributionMode::Push,
+            prng_seed: Binary::from(b"seed".to_vec()),
+            hide_recipients: false,
+            timelock: None,
+            locked_recipients: false,
+            registration_policy: RegistrationPolicy::AdminOnly,
+            stats_epoch_length: None,
+            max_forward_depth: None,
+            callback: None,
+        };
+        let response = init(&mut deps, env, msg).unwrap();
+        assert_eq!(response.messages.len(), 2);
+        let msg = HandleMsg::RegisterToken {
+            snip20_addr: HumanAddr::from("stable"),
+            snip20_hash: String::from("stable hash"),
+            dist_info: None,
+        };
+        let response = handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        assert_eq!(response.messages.len(), 2);
+        match &response.messages[1] {
+            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. }) => {
+                assert_eq!(contract_addr, &HumanAddr::from("stable"));
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+
+        deps.querier.balance = 42;
+        let msg = HandleMsg::SetViewingKey { key: String::from("key"), padding: None };
+        handle(&mut deps, mock_env("admin", &[]), msg.clone()).unwrap();
+        handle(&mut deps, mock_env("alice", &[]), msg).unwrap();
+        let query_msg = |address: &str| QueryMsg::QueryContractBalances {
+            address: HumanAddr::from(address),
+            key: String::from("key"),
+        };
+        assert!(query(&deps, query_msg("alice")).is_err());
+        // a token rejecting the contract's key does not hide the other balances
+        let msg = HandleMsg::RegisterToken {
+            snip20_addr: HumanAddr::from("stubborn"),
+            snip20_hash: String::from("stubborn hash"),
+            dist_info: None,
+        };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        match from_binary(&query(&deps, query_msg("admin")).unwrap()).unwrap() {
+            QueryAnswer::ContractBalances { balances, errors } => {
+                let tokens: Vec<(HumanAddr, u128)> =
+                    balances.into_iter().map(|b| (b.token, b.amount.u128())).collect();
+                assert_eq!(
+                    tokens,
+                    vec![(HumanAddr::from("sscrt"), 42), (HumanAddr::from("stable"), 42)]
+                );
+                assert_eq!(errors.len(), 1);
+                assert_eq!(errors[0].token, HumanAddr::from("stubborn"));
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn forwards_are_recorded_in_the_ledger() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(None));
+        let mut env = mock_env("sscrt", &[]);
+        env.block.height = 100;
+        forward_funds(&mut deps, env, HumanAddr::from("donor"), Uint128(100), ReceiveMsg::default()).unwrap();
+        handle(&mut deps, mock_env("customer", &[coin(10, "uscrt")]), HandleMsg::ForwardNative { distribution: None, split_id: None }).unwrap();
+        forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(1_000), ReceiveMsg::default()).unwrap();
+
+        let msg = HandleMsg::SetViewingKey { key: String::from("key"), padding: None };
+        handle(&mut deps, mock_env("admin", &[]), msg.clone()).unwrap();
+        handle(&mut deps, mock_env("bob", &[]), msg).unwrap();
+        let history = |address: &str, token: Option<&str>, page: Option<u32>| QueryMsg::QueryForwardHistory {
+            address: HumanAddr::from(address),
+            key: String::from("key"),
+            token: token.map(String::from),
+            page,
+            page_size: 1,
+        };
+        assert!(query(&deps, history("bob", None, None)).is_err());
+        match from_binary(&query(&deps, history("admin", None, None)).unwrap()).unwrap() {
+            QueryAnswer::ForwardHistory { forwards, total } => {
+                assert_eq!(total, 3);
+                assert_eq!(forwards[0].forward_id, 2);
+                assert_eq!(forwards[0].amount, Uint128(1_000));
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+        match from_binary(&query(&deps, history("admin", Some("sscrt"), Some(1))).unwrap()).unwrap() {
+            QueryAnswer::ForwardHistory { forwards, total } => {
+                assert_eq!(total, 2);
+                let forward = &forwards[0];
+                assert_eq!(forward.forward_id, 0);
+                assert_eq!(forward.from, HumanAddr::from("donor"));
+                assert_eq!(forward.dust, Uint128(1));
+                assert_eq!(forward.block_height, 100);
+                assert_eq!(forward.payouts[1].recipient, HumanAddr::from("bob"));
+                assert_eq!(forward.payouts[1].amount, Uint128(34));
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+
+        // recipients see their own payouts of a token
+        let query_msg = QueryMsg::QueryPayouts {
+            address: HumanAddr::from("bob"),
+            key: String::from("key"),
+            token: Some(String::from("uscrt")),
+            page: None,
+            page_size: 10,
+        };
+        match from_binary(&query(&deps, query_msg).unwrap()).unwrap() {
+            QueryAnswer::Payouts { payouts, total } => {
+                assert_eq!(total, 1);
+                assert_eq!(payouts[0].amount, Uint128(4));
+                assert_eq!(payouts[0].forward_id, 1);
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn stats_total_forwards_per_token_and_epoch() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(None));
+        let mut env = mock_env("sscrt", &[]);
+        env.block.height = 100;
+        forward_funds(&mut deps, env.clone(), HumanAddr::from("donor"), Uint128(100), ReceiveMsg::default()).unwrap();
+        forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(1_000), ReceiveMsg::default()).unwrap();
+        env.block.height = 150_000;
+        forward_funds(&mut deps, env, HumanAddr::from("donor"), Uint128(10), ReceiveMsg::default()).unwrap();
+
+        let msg = HandleMsg::SetViewingKey { key: String::from("key"), padding: None };
+        handle(&mut deps, mock_env("admin", &[]), msg.clone()).unwrap();
+        handle(&mut deps, mock_env("bob", &[]), msg).unwrap();
+        let stats = |address: &str, epochs: Option<HeightRange>| QueryMsg::QueryStats {
+            address: HumanAddr::from(address),
+            key: String::from("key"),
+            token: String::from("sscrt"),
+            epochs,
+        };
+        assert!(query(&deps, stats("bob", None)).is_err());
+        match from_binary(&query(&deps, stats("admin", None)).unwrap()).unwrap() {
+            QueryAnswer::Stats { received, dust, forwards, distributed, unclaimed, epoch_length, epochs, .. } => {
+                assert_eq!(received, Uint128(1_110));
+                assert_eq!(dust, Uint128(3));
+                assert_eq!(forwards, 3);
+                assert_eq!(distributed[1], Payout { recipient: HumanAddr::from("bob"), amount: Uint128(372) });
+                assert_eq!(unclaimed, Uint128(0));
+                assert_eq!(epoch_length, 100_000);
+                assert!(epochs.is_empty());
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+
+        let range = HeightRange { start_height: 50, end_height: 250_000 };
+        match from_binary(&query(&deps, stats("admin", Some(range))).unwrap()).unwrap() {
+            QueryAnswer::Stats { epochs, .. } => {
+                assert_eq!(epochs.len(), 2);
+                assert_eq!(epochs[0].received, Uint128(1_100));
+                assert_eq!(epochs[0].dust, Uint128(2));
+                assert_eq!(epochs[0].forwards, 2);
+                assert_eq!(epochs[0].distributed[1].amount, Uint128(368));
+                assert_eq!(epochs[1].epoch, 1);
+                assert_eq!(epochs[1].start_height, 100_000);
+                assert_eq!(epochs[1].end_height, 199_999);
+                assert_eq!(epochs[1].received, Uint128(10));
+                assert_eq!(epochs[1].distributed[1].amount, Uint128(4));
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+
+        // queries can not span too many epochs
+        let range = HeightRange { start_height: 0, end_height: 100_000 * MAX_QUERY_EPOCHS };
+        assert!(query(&deps, stats("admin", Some(range))).is_err());
+    }
+
+    #[test]
+    pub fn stats_show_pull_mode_credits_as_unclaimed() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(None));
+        let msg = HandleMsg::SetDistributionMode { mode: DistributionMode::Pull };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(1_000), ReceiveMsg::default()).unwrap();
+
+        let msg = HandleMsg::SetViewingKey { key: String::from("key"), padding: None };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        let stats = QueryMsg::QueryStats {
+            address: HumanAddr::from("admin"),
+            key: String::from("key"),
+            token: String::from("sscrt"),
+            epochs: None,
+        };
+        // credits are distributed, but nothing has been claimed
+        match from_binary(&query(&deps, stats.clone()).unwrap()).unwrap() {
+            QueryAnswer::Stats { distributed, unclaimed, .. } => {
+                assert_eq!(distributed[1], Payout { recipient: HumanAddr::from("bob"), amount: Uint128(334) });
+                assert_eq!(unclaimed, Uint128(1_000));
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+
+        let msg = HandleMsg::Claim { tokens: vec![HumanAddr::from("sscrt")] };
+        handle(&mut deps, mock_env("bob", &[]), msg).unwrap();
+        match from_binary(&query(&deps, stats).unwrap()).unwrap() {
+            QueryAnswer::Stats { distributed, unclaimed, .. } => {
+                assert_eq!(distributed[1].amount, Uint128(334));
+                assert_eq!(unclaimed, Uint128(666));
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn handles_log_structured_attributes() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(None));
+        let dist_hash = |dist: &RoyaltyInfo| Binary::from(&sha_256(to_binary(dist).unwrap().as_slice())[..]).to_base64();
+
+        let response = forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(100), ReceiveMsg::default()).unwrap();
+        assert_eq!(
+            response.log,
+            vec![
+                log("action", "forward"),
+                log("sender", "sscrt"),
+                log("token", "sscrt"),
+                log("from", "donor"),
+                log("amount", "100"),
+                log("recipient_count", "3"),
+                log("dust", "1"),
+                log("distribution_hash", dist_hash(&three_way_split(None))),
+            ]
+        );
+        let response = handle(&mut deps, mock_env("customer", &[coin(10, "uscrt")]), HandleMsg::ForwardNative { distribution: None, split_id: None }).unwrap();
+        assert_eq!(
+            response.log,
+            vec![
+                log("action", "forward_native"),
+                log("sender", "customer"),
+                log("token", "uscrt"),
+                log("from", "customer"),
+                log("amount", "10"),
+                log("recipient_count", "3"),
+                log("dust", "1"),
+                log("distribution_hash", dist_hash(&three_way_split(None))),
+            ]
+        );
+
+        let msg = HandleMsg::RegisterToken {
+            snip20_addr: HumanAddr::from("stable"),
+            snip20_hash: String::from("stable hash"),
+            dist_info: None,
+        };
+        let response = handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        assert_eq!(
+            response.log,
+            vec![
+                log("action", "register_token"),
+                log("sender", "admin"),
+                log("token", "stable"),
+                log("symbol", "STABLE"),
+            ]
+        );
+
+        let dave_only = RoyaltyInfo {
+            decimal_places_in_rates: 0,
+            royalties: vec![Royalty { recipient: HumanAddr::from("dave"), rate: 1, code_hash: None, msg: None }],
+            remainder_recipient: None,
+        };
+        let msg = HandleMsg::ChangeDistribution { dist_info: dave_only.clone() };
+        let response = handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        assert_eq!(
+            response.log,
+            vec![
+                log("action", "change_distribution"),
+                log("sender", "admin"),
+                log("distribution_hash", dist_hash(&dave_only)),
+            ]
+        );
+
+        let msg = HandleMsg::ProposeAdmin { admin_addr: HumanAddr::from("new admin") };
+        let response = handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        assert_eq!(
+            response.log,
+            vec![
+                log("action", "propose_admin"),
+                log("sender", "admin"),
+                log("new_admin", "new admin"),
+            ]
+        );
+        let response = handle(&mut deps, mock_env("new admin", &[]), HandleMsg::AcceptAdmin {}).unwrap();
+        assert_eq!(
+            response.log,
+            vec![
+                log("action", "accept_admin"),
+                log("sender", "new admin"),
+                log("new_admin", "new admin"),
+            ]
+        );
+
+        // proposals log the proposed action, and the action itself once approved
+        let msg = HandleMsg::ChangeAdmins {
+            admins: vec![HumanAddr::from("new admin"), HumanAddr::from("co admin")],
+            threshold: 2,
+        };
+        let response = handle(&mut deps, mock_env("new admin", &[]), msg).unwrap();
+        assert_eq!(
+            response.log,
+            vec![
+                log("action", "change_admins"),
+                log("sender", "new admin"),
+                log("admins", "new admin,co admin"),
+                log("threshold", "2"),
+            ]
+        );
+        let msg = HandleMsg::SetHideRecipients { hide_recipients: true };
+        let response = handle(&mut deps, mock_env("new admin", &[]), msg).unwrap();
+        assert_eq!(
+            response.log,
+            vec![
+                log("action", "admin_proposal"),
+                log("sender", "new admin"),
+                log("proposed_action", "set_hide_recipients"),
+                log("proposal_id", "0"),
+                log("approvals", "1"),
+            ]
+        );
+        let response = handle(&mut deps, mock_env("co admin", &[]), HandleMsg::ApproveProposal { proposal_id: 0 }).unwrap();
+        assert_eq!(
+            response.log,
+            vec![
+                log("action", "set_hide_recipients"),
+                log("sender", "co admin"),
+                log("hide_recipients", "true"),
+                log("proposal_id", "0"),
+            ]
+        );
     }
 
+    #[test]
+    pub fn handles_answer_what_they_did() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(None));
+
+        // snip20s and the native coins sent with them are each a forward
+        let msg = HandleMsg::Receive {
+            sender: HumanAddr::from("donor"),
+            from: HumanAddr::from("donor"),
+            amount: Uint128(100),
+            msg: None,
+        };
+        let response = handle(&mut deps, mock_env("sscrt", &[coin(10, "uscrt")]), msg).unwrap();
+        let data = response.data.unwrap();
+        assert_eq!(data.len() % BLOCK_SIZE, 0);
+        match from_binary(&data).unwrap() {
+            HandleAnswer::ForwardFunds { forwards } => {
+                assert_eq!(forwards.len(), 2);
+                assert_eq!(forwards[0].forward_id, 0);
+                assert_eq!(forwards[0].token, String::from("sscrt"));
+                assert_eq!(forwards[0].dust, Uint128(1));
+                assert_eq!(
+                    forwards[0].payouts[1],
+                    ForwardPayout { recipient: Some(HumanAddr::from("bob")), amount: Uint128(34) }
+                );
+                assert_eq!(forwards[1].forward_id, 1);
+                assert_eq!(forwards[1].token, String::from("uscrt"));
+                assert_eq!(forwards[1].amount, Uint128(10));
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+
+        // hidden recipients stay hidden
+        let msg = HandleMsg::SetHideRecipients { hide_recipients: true };
+        match from_binary(&handle(&mut deps, mock_env("admin", &[]), msg).unwrap().data.unwrap()).unwrap() {
+            HandleAnswer::SetHideRecipients { status } => assert_eq!(status, ResponseStatus::Success),
+            other => panic!("unexpected answer {:?}", other),
+        }
+        let response = handle(&mut deps, mock_env("customer", &[coin(10, "uscrt")]), HandleMsg::ForwardNative { distribution: None, split_id: None }).unwrap();
+        match from_binary(&response.data.unwrap()).unwrap() {
+            HandleAnswer::ForwardFunds { forwards } => {
+                assert!(forwards[0].payouts.iter().all(|p| p.recipient.is_none()));
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+
+        // claims answer the amounts withdrawn
+        let msg = HandleMsg::SetDistributionMode { mode: DistributionMode::Pull };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(100), ReceiveMsg::default()).unwrap();
+        let msg = HandleMsg::Claim { tokens: vec![HumanAddr::from("sscrt")] };
+        match from_binary(&handle(&mut deps, mock_env("bob", &[]), msg).unwrap().data.unwrap()).unwrap() {
+            HandleAnswer::Claim { claimed } => {
+                assert_eq!(claimed, vec![TokenBalance { token: HumanAddr::from("sscrt"), amount: Uint128(34) }]);
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn receive_msg_selects_a_named_distribution() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(None));
+
+        let tips = RoyaltyInfo {
+            decimal_places_in_rates: 0,
+            royalties: vec![Royalty { recipient: HumanAddr::from("dave"), rate: 1, code_hash: None, msg: None }],
+            remainder_recipient: None,
+        };
+        let msg = HandleMsg::SetNamedDistribution { name: String::from("tips"), dist_info: Some(tips) };
+        match from_binary(&handle(&mut deps, mock_env("admin", &[]), msg).unwrap().data.unwrap()).unwrap() {
+            HandleAnswer::SetNamedDistribution { status } => assert_eq!(status, ResponseStatus::Success),
+            other => panic!("unexpected answer {:?}", other),
+        }
+        match from_binary(&query(&deps, QueryMsg::QueryConfig {}).unwrap()).unwrap() {
+            QueryAnswer::Config { distributions, .. } => assert_eq!(distributions, vec![String::from("tips")]),
+            other => panic!("unexpected answer {:?}", other),
+        }
+
+        // the send payload selects the distribution
+        let msg = HandleMsg::Receive {
+            sender: HumanAddr::from("donor"),
+            from: HumanAddr::from("donor"),
+            amount: Uint128(500),
+            msg: Some(Binary::from(br#"{"distribution":"tips"}"#.to_vec())),
+        };
+        let response = handle(&mut deps, mock_env("sscrt", &[]), msg).unwrap();
+        assert_eq!(transfers(&response.messages), vec![(HumanAddr::from("dave"), 500)]);
+        assert!(response.log.contains(&log("distribution", "tips")));
+        let msg = HandleMsg::ForwardNative { distribution: Some(String::from("tips")), split_id: None };
+        let response = handle(&mut deps, mock_env("customer", &[coin(10, "uscrt")]), msg).unwrap();
+        assert_eq!(
+            response.messages,
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("dave"),
+                amount: vec![coin(10, "uscrt")],
+            })]
+        );
+
+        // without a payload the default is used, and unknown names are rejected
+        let msg = HandleMsg::Receive {
+            sender: HumanAddr::from("donor"),
+            from: HumanAddr::from("donor"),
+            amount: Uint128(500),
+            msg: None,
+        };
+        let response = handle(&mut deps, mock_env("sscrt", &[]), msg).unwrap();
+        assert_eq!(transfers(&response.messages).len(), 3);
+        let receive_msg = ReceiveMsg { distribution: Some(String::from("grants")), split_id: None, hops: 0 };
+        let result = forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(500), receive_msg);
+        match result {
+            Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "There is no distribution named grants"),
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        // payloads meant for other contracts, and misspelled selections, are rejected
+        for payload in vec![&br#"{"swap":{"min_output":"5"}}"#[..], &br#"{"distributon":"tips"}"#[..], &b"not json"[..]] {
+            let msg = HandleMsg::Receive {
+                sender: HumanAddr::from("donor"),
+                from: HumanAddr::from("donor"),
+                amount: Uint128(500),
+                msg: Some(Binary::from(payload.to_vec())),
+            };
+            match handle(&mut deps, mock_env("sscrt", &[]), msg) {
+                Err(StdError::GenericErr { msg, .. }) => {
+                    assert_eq!(msg, "The send msg must be empty or only select a distribution or split_id");
+                }
+                other => panic!("unexpected result {:?}", other),
+            }
+        }
+
+        // removing it stops it being selected
+        let msg = HandleMsg::SetNamedDistribution { name: String::from("tips"), dist_info: None };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        let receive_msg = ReceiveMsg { distribution: Some(String::from("tips")), split_id: None, hops: 0 };
+        assert!(forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(500), receive_msg).is_err());
+        let query_msg = QueryMsg::QueryDist { token: None, distribution: Some(String::from("tips")), height: None, time: None };
+        match from_binary(&query(&deps, query_msg).unwrap()).unwrap() {
+            QueryAnswer::RoyaltyInfo { royalty_info, .. } => assert!(royalty_info.is_none()),
+            other => panic!("unexpected answer {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn anyone_can_create_splits_that_receive_msgs_select() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(None));
+
+        let grants = RoyaltyInfo {
+            decimal_places_in_rates: 1,
+            royalties: vec![
+                Royalty { recipient: HumanAddr::from("dave"), rate: 7, code_hash: None, msg: None },
+                Royalty { recipient: HumanAddr::from("erin"), rate: 3, code_hash: None, msg: None },
+            ],
+            remainder_recipient: None,
+        };
+        let msg = HandleMsg::CreateSplit { name: String::from("grants"), dist_info: grants, admin: None };
+        let response = handle(&mut deps, mock_env("stranger", &[]), msg).unwrap();
+        match from_binary(&response.data.unwrap()).unwrap() {
+            HandleAnswer::CreateSplit { split_id } => assert_eq!(split_id, 0),
+            other => panic!("unexpected answer {:?}", other),
+        }
+
+        // the payload routes funds to the split
+        let msg = HandleMsg::Receive {
+            sender: HumanAddr::from("donor"),
+            from: HumanAddr::from("donor"),
+            amount: Uint128(100),
+            msg: Some(Binary::from(br#"{"split_id":0}"#.to_vec())),
+        };
+        let response = handle(&mut deps, mock_env("sscrt", &[]), msg).unwrap();
+        assert_eq!(
+            transfers(&response.messages),
+            vec![(HumanAddr::from("dave"), 70), (HumanAddr::from("erin"), 30)]
+        );
+        assert!(response.log.contains(&log("split_id", 0)));
+        let receive_msg = ReceiveMsg { distribution: None, split_id: Some(1), hops: 0 };
+        let result = forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(100), receive_msg);
+        match result {
+            Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Split 1 does not exist"),
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        // only the split's admin can change it, and the contract admin is not its admin
+        let halves = RoyaltyInfo {
+            decimal_places_in_rates: 1,
+            royalties: vec![
+                Royalty { recipient: HumanAddr::from("dave"), rate: 5, code_hash: None, msg: None },
+                Royalty { recipient: HumanAddr::from("erin"), rate: 5, code_hash: None, msg: None },
+            ],
+            remainder_recipient: None,
+        };
+        let msg = HandleMsg::ChangeSplitDistribution { split_id: 0, dist_info: halves.clone() };
+        assert!(handle(&mut deps, mock_env("admin", &[]), msg).is_err());
+        let msg = HandleMsg::ProposeSplitAdmin { split_id: 0, admin_addr: HumanAddr::from("treasurer") };
+        handle(&mut deps, mock_env("stranger", &[]), msg).unwrap();
+        handle(&mut deps, mock_env("treasurer", &[]), HandleMsg::AcceptSplitAdmin { split_id: 0 }).unwrap();
+        let msg = HandleMsg::ChangeSplitDistribution { split_id: 0, dist_info: halves.clone() };
+        assert!(handle(&mut deps, mock_env("stranger", &[]), msg).is_err());
+        let msg = HandleMsg::ChangeSplitDistribution { split_id: 0, dist_info: halves };
+        handle(&mut deps, mock_env("treasurer", &[]), msg).unwrap();
+
+        let msg = HandleMsg::ForwardNative { distribution: None, split_id: Some(0) };
+        let response = handle(&mut deps, mock_env("customer", &[coin(10, "uscrt")]), msg).unwrap();
+        assert_eq!(response.messages.len(), 2);
+
+        let msg = HandleMsg::CreateSplit {
+            name: String::from("tips"),
+            dist_info: three_way_split(None),
+            admin: Some(HumanAddr::from("alice")),
+        };
+        handle(&mut deps, mock_env("stranger", &[]), msg).unwrap();
+        let query_msg = QueryMsg::QuerySplits { page: Some(1), page_size: 1 };
+        match from_binary(&query(&deps, query_msg).unwrap()).unwrap() {
+            QueryAnswer::Splits { splits, total } => {
+                assert_eq!(total, 2);
+                assert_eq!(splits.len(), 1);
+                assert_eq!(splits[0].split_id, 1);
+                assert_eq!(splits[0].name, String::from("tips"));
+                assert_eq!(splits[0].admin, HumanAddr::from("alice"));
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+        match from_binary(&query(&deps, QueryMsg::QuerySplit { split_id: 0 }).unwrap()).unwrap() {
+            QueryAnswer::Split { split } => {
+                assert_eq!(split.admin, HumanAddr::from("treasurer"));
+                assert_eq!(split.pending_admin, None);
+                assert_eq!(split.royalty_info.royalties[1].rate, 5);
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn split_changes_wait_for_the_timelock_and_locked_recipients() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, three_way_split(None));
+        let msg = HandleMsg::SetTimelock { timelock: Some(Timelock::Blocks(100)) };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        let msg = HandleMsg::CreateSplit {
+            name: String::from("tips"),
+            dist_info: three_way_split(None),
+            admin: Some(HumanAddr::from("treasurer")),
+        };
+        handle(&mut deps, mock_env("stranger", &[]), msg).unwrap();
+        let tip = ReceiveMsg { distribution: None, split_id: Some(0), hops: 0 };
+        let at = |sender: &str, blocks: u64| {
+            let mut env = mock_env(sender, &[]);
+            env.block.height += blocks;
+            env
+        };
+
+        // the change is shown but the old distribution is still used
+        let dave_only = RoyaltyInfo {
+            decimal_places_in_rates: 0,
+            royalties: vec![Royalty { recipient: HumanAddr::from("dave"), rate: 1, code_hash: None, msg: None }],
+            remainder_recipient: None,
+        };
+        let msg = HandleMsg::ChangeSplitDistribution { split_id: 0, dist_info: dave_only };
+        handle(&mut deps, mock_env("treasurer", &[]), msg).unwrap();
+        match from_binary(&query(&deps, QueryMsg::QuerySplit { split_id: 0 }).unwrap()).unwrap() {
+            QueryAnswer::Split { split } => {
+                assert_eq!(split.royalty_info.royalties.len(), 3);
+                assert_eq!(split.pending.unwrap().activation, Activation::Height(12_445));
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+        let response = forward_funds(&mut deps, at("sscrt", 0), HumanAddr::from("donor"), Uint128(100), tip.clone()).unwrap();
+        assert_eq!(transfers(&response.messages).len(), 3);
+        let response = forward_funds(&mut deps, at("sscrt", 100), HumanAddr::from("donor"), Uint128(100), tip.clone()).unwrap();
+        assert_eq!(transfers(&response.messages), vec![(HumanAddr::from("dave"), 100)]);
 
-}
\ No newline at end of file
+        // recipients can move their share of a split
+        let msg = HandleMsg::ChangeMyRecipientAddress {
+            new_addr: HumanAddr::from("dave2"),
+            require_acceptance: false,
+        };
+        handle(&mut deps, at("dave", 100), msg).unwrap();
+        let response = forward_funds(&mut deps, at("sscrt", 100), HumanAddr::from("donor"), Uint128(100), tip.clone()).unwrap();
+        assert_eq!(transfers(&response.messages), vec![(HumanAddr::from("dave2"), 100)]);
+
+        // lowering a locked recipient's rate in a split needs their approval, then the timelock
+        handle(&mut deps, at("admin", 100), HandleMsg::LockRecipients {}).unwrap();
+        let msg = HandleMsg::ChangeSplitDistribution { split_id: 0, dist_info: three_way_split(None) };
+        let response = handle(&mut deps, at("treasurer", 100), msg).unwrap();
+        let proposal_id = match from_binary(&response.data.unwrap()).unwrap() {
+            HandleAnswer::DistributionProposal { proposal_id, awaiting } => {
+                assert_eq!(awaiting, 1);
+                proposal_id
+            }
+            other => panic!("unexpected answer {:?}", other),
+        };
+        let msg = HandleMsg::SetViewingKey { key: String::from("key"), padding: None };
+        handle(&mut deps, mock_env("treasurer", &[]), msg.clone()).unwrap();
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        let query_msg = QueryMsg::QueryDistributionProposal {
+            address: HumanAddr::from("treasurer"),
+            key: String::from("key"),
+            proposal_id,
+        };
+        match from_binary(&query(&deps, query_msg).unwrap()).unwrap() {
+            QueryAnswer::DistributionProposal { proposal } => {
+                assert_eq!(proposal.split_id, Some(0));
+                assert_eq!(proposal.required, vec![HumanAddr::from("dave2")]);
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+        let approve = HandleMsg::ApproveDistributionChange { proposal_id };
+        handle(&mut deps, at("dave2", 100), approve).unwrap();
+        let response = forward_funds(&mut deps, at("sscrt", 150), HumanAddr::from("donor"), Uint128(100), tip.clone()).unwrap();
+        assert_eq!(transfers(&response.messages), vec![(HumanAddr::from("dave2"), 100)]);
+        let response = forward_funds(&mut deps, at("sscrt", 200), HumanAddr::from("donor"), Uint128(100), tip).unwrap();
+        assert_eq!(transfers(&response.messages).len(), 3);
+
+        // split forwards are only in the split's own ledger, which only its admin can see
+        let history = QueryMsg::QueryForwardHistory {
+            address: HumanAddr::from("admin"),
+            key: String::from("key"),
+            token: None,
+            page: None,
+            page_size: 10,
+        };
+        match from_binary(&query(&deps, history).unwrap()).unwrap() {
+            QueryAnswer::ForwardHistory { total, .. } => assert_eq!(total, 0),
+            other => panic!("unexpected answer {:?}", other),
+        }
+        let split_history = |address: &str| QueryMsg::QuerySplitForwards {
+            address: HumanAddr::from(address),
+            key: String::from("key"),
+            split_id: 0,
+            page: None,
+            page_size: 2,
+        };
+        assert!(query(&deps, split_history("admin")).is_err());
+        match from_binary(&query(&deps, split_history("treasurer")).unwrap()).unwrap() {
+            QueryAnswer::ForwardHistory { forwards, total } => {
+                assert_eq!(total, 5);
+                assert_eq!(forwards[0].forward_id, 4);
+                assert_eq!(forwards[0].payouts.len(), 3);
+                assert_eq!(forwards[1].payouts[0].recipient, HumanAddr::from("dave2"));
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn factory_instantiates_and_indexes_forwarders() {
+        let mut factory_deps = mock_deps();
+        let msg = factory::msg::InitMsg {
+            admin: None,
+            forwarder_code_id: 7,
+            forwarder_code_hash: String::from("forwarder hash"),
+            entropy: String::from("entropy"),
+        };
+        factory::contract::init(&mut factory_deps, mock_env("deployer", &[]), msg).unwrap();
+
+        let forwarder = InitMsg {
+            admin: HumanAddr::from("admin"),
+            co_admins: vec![HumanAddr::from("partner")],
+            admin_threshold: None,
+            dist_info: three_way_split(None),
+            sscrt_addr: HumanAddr::from("sscrt"),
+            sscrt_hash: String::from("sscrt hash"),
+            distribution_mode: DistributionMode::Push,
+            prng_seed: Binary::from(b"seed".to_vec()),
+            hide_recipients: false,
+            timelock: None,
+            locked_recipients: false,
+            registration_policy: RegistrationPolicy::AdminOnly,
+            stats_epoch_length: None,
+            max_forward_depth: None,
+            callback: None,
+        };
+        let msg = factory::msg::HandleMsg::CreateForwarder { label: String::from("shop"), forwarder };
+        let response = factory::contract::handle(&mut factory_deps, mock_env("creator", &[]), msg).unwrap();
+        let init_msg: InitMsg = match &response.messages[0] {
+            CosmosMsg::Wasm(WasmMsg::Instantiate { code_id, callback_code_hash, msg, label, .. }) => {
+                assert_eq!(*code_id, 7);
+                assert_eq!(callback_code_hash, "forwarder hash");
+                assert_eq!(label, "shop");
+                from_binary(msg).unwrap()
+            }
+            other => panic!("unexpected message {:?}", other),
+        };
+
+        // the new forwarder calls back to the factory when instantiated
+        let mut deps = mock_deps();
+        let response = init(&mut deps, mock_env("creator", &[]), init_msg).unwrap();
+        let callback = match response.messages.last().unwrap() {
+            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, msg, .. }) => {
+                assert_eq!(*contract_addr, HumanAddr::from("cosmos2contract"));
+                msg.clone()
+            }
+            other => panic!("unexpected message {:?}", other),
+        };
+        let forged = factory::msg::HandleMsg::RegisterForwarder { forwarder_id: 0, key: String::from("guess") };
+        assert!(factory::contract::handle(&mut factory_deps, mock_env("impostor", &[]), forged).is_err());
+        let callback: factory::msg::HandleMsg = from_binary(&callback).unwrap();
+        factory::contract::handle(&mut factory_deps, mock_env("shop forwarder", &[]), callback.clone()).unwrap();
+        assert!(factory::contract::handle(&mut factory_deps, mock_env("impostor", &[]), callback).is_err());
+
+        for query_msg in vec![
+            factory::msg::QueryMsg::QueryForwardersByCreator { creator: HumanAddr::from("creator"), page: None, page_size: 10 },
+            factory::msg::QueryMsg::QueryForwardersByInitialAdmin { admin: HumanAddr::from("partner"), page: None, page_size: 10 },
+        ] {
+            match from_binary(&factory::contract::query(&factory_deps, query_msg).unwrap()).unwrap() {
+                factory::msg::QueryAnswer::Forwarders { forwarders, total } => {
+                    assert_eq!(total, 1);
+                    assert_eq!(forwarders[0].address, HumanAddr::from("shop forwarder"));
+                    assert_eq!(forwarders[0].label, String::from("shop"));
+                    assert_eq!(forwarders[0].creator, HumanAddr::from("creator"));
+                }
+                other => panic!("unexpected answer {:?}", other),
+            }
+        }
+        let query_msg = factory::msg::QueryMsg::QueryForwardersByInitialAdmin { admin: HumanAddr::from("creator"), page: None, page_size: 10 };
+        match from_binary(&factory::contract::query(&factory_deps, query_msg).unwrap()).unwrap() {
+            factory::msg::QueryAnswer::Forwarders { total, .. } => assert_eq!(total, 0),
+            other => panic!("unexpected answer {:?}", other),
+        }
+        let query_msg = factory::msg::QueryMsg::QueryForwardersByCreator { creator: HumanAddr::from("creator"), page: Some(1), page_size: 1 };
+        match from_binary(&factory::contract::query(&factory_deps, query_msg).unwrap()).unwrap() {
+            factory::msg::QueryAnswer::Forwarders { forwarders, total } => {
+                assert!(forwarders.is_empty());
+                assert_eq!(total, 1);
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+
+        // only the deployer can point the factory at new forwarder code
+        let msg = factory::msg::HandleMsg::SetForwarderCode { code_id: 8, code_hash: String::from("new hash") };
+        assert!(factory::contract::handle(&mut factory_deps, mock_env("creator", &[]), msg.clone()).is_err());
+        factory::contract::handle(&mut factory_deps, mock_env("deployer", &[]), msg).unwrap();
+        match from_binary(&factory::contract::query(&factory_deps, factory::msg::QueryMsg::QueryConfig {}).unwrap()).unwrap() {
+            factory::msg::QueryAnswer::Config { admin, forwarder_code_id, forwarder_code_hash } => {
+                assert_eq!(admin, HumanAddr::from("deployer"));
+                assert_eq!(forwarder_code_id, 8);
+                assert_eq!(forwarder_code_hash, String::from("new hash"));
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn contract_recipients_are_sent_their_share() {
+        let mut deps = mock_deps();
+        let pool_msg = Binary::from(br#"{"stake":{}}"#.to_vec());
+        init_with(&mut deps, RoyaltyInfo {
+            decimal_places_in_rates: 1,
+            royalties: vec![
+                Royalty { recipient: HumanAddr::from("alice"), rate: 6, code_hash: None, msg: None },
+                Royalty {
+                    recipient: HumanAddr::from("pool"),
+                    rate: 4,
+                    code_hash: Some(String::from("pool hash")),
+                    msg: Some(pool_msg.clone()),
+                },
+            ],
+            remainder_recipient: None,
+        });
+
+        let response = forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(100), ReceiveMsg::default()).unwrap();
+        let expected = send_msg(
+            HumanAddr::from("pool"),
+            Uint128(40),
+            Some(pool_msg),
+            None,
+            BLOCK_SIZE,
+            String::from("sscrt hash"),
+            HumanAddr::from("sscrt"),
+        )
+        .unwrap();
+        assert_eq!(response.messages.len(), 2);
+        assert_eq!(response.messages[1], expected);
+
+        // contracts can not claim, so they are still sent their share in pull mode
+        let msg = HandleMsg::SetDistributionMode { mode: DistributionMode::Pull };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+        let response = forward_funds(&mut deps, mock_env("sscrt", &[]), HumanAddr::from("donor"), Uint128(100), ReceiveMsg::default()).unwrap();
+        assert_eq!(response.messages, vec![expected]);
+
+        // a msg can only be sent to a contract
+        let dist_info = RoyaltyInfo {
+            decimal_places_in_rates: 0,
+            royalties: vec![Royalty {
+                recipient: HumanAddr::from("alice"),
+                rate: 1,
+                code_hash: None,
+                msg: Some(Binary::from(b"{}".to_vec())),
+            }],
+            remainder_recipient: None,
+        };
+        assert!(handle(&mut deps, mock_env("admin", &[]), HandleMsg::ChangeDistribution { dist_info }).is_err());
+    }
+
+    #[test]
+    pub fn cycles_without_a_msg_are_limited_by_hops() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, RoyaltyInfo {
+            decimal_places_in_rates: 0,
+            royalties: vec![Royalty {
+                recipient: HumanAddr::from("forwarder b"),
+                rate: 1,
+                code_hash: Some(String::from("forwarder hash")),
+                msg: None,
+            }],
+            remainder_recipient: None,
+        });
+
+        // forwarder b is set up the same way and sends everything back, adding its own hop
+        let mut msg = None;
+        for hops in (0..10).step_by(2) {
+            let receive = HandleMsg::Receive {
+                sender: HumanAddr::from("forwarder b"),
+                from: HumanAddr::from("forwarder b"),
+                amount: Uint128(100),
+                msg,
+            };
+            let response = handle(&mut deps, mock_env("sscrt", &[]), receive).unwrap();
+            let expected = send_msg(
+                HumanAddr::from("forwarder b"),
+                Uint128(100),
+                Some(to_binary(&ReceiveMsg { distribution: None, split_id: None, hops: hops + 1 }).unwrap()),
+                None,
+                BLOCK_SIZE,
+                String::from("sscrt hash"),
+                HumanAddr::from("sscrt"),
+            )
+            .unwrap();
+            assert_eq!(response.messages, vec![expected]);
+            msg = Some(to_binary(&ReceiveMsg { hops: hops + 2, ..ReceiveMsg::default() }).unwrap());
+        }
+        let receive = HandleMsg::Receive {
+            sender: HumanAddr::from("forwarder b"),
+            from: HumanAddr::from("forwarder b"),
+            amount: Uint128(100),
+            msg,
+        };
+        match handle(&mut deps, mock_env("sscrt", &[]), receive) {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!(msg, "Funds have been forwarded through too many contracts")
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn nested_forwards_are_limited_by_hops() {
+        let mut deps = mock_deps();
+        init_with(&mut deps, RoyaltyInfo {
+            decimal_places_in_rates: 1,
+            royalties: vec![
+                Royalty { recipient: HumanAddr::from("alice"), rate: 5, code_hash: None, msg: None },
+                Royalty {
+                    recipient: HumanAddr::from("forwarder b"),
+                    rate: 4,
+                    code_hash: Some(String::from("forwarder hash")),
+                    msg: Some(Binary::from(b"{}".to_vec())),
+                },
+                Royalty {
+                    recipient: HumanAddr::from("pool"),
+                    rate: 1,
+                    code_hash: Some(String::from("pool hash")),
+                    msg: None,
+                },
+            ],
+            remainder_recipient: None,
+        });
+
+        // a forwarder recipient is sent the hop count of the received funds plus one
+        let msg = HandleMsg::Receive {
+            sender: HumanAddr::from("forwarder a"),
+            from: HumanAddr::from("forwarder a"),
+            amount: Uint128(100),
+            msg: Some(Binary::from(br#"{"hops":3}"#.to_vec())),
+        };
+        let response = handle(&mut deps, mock_env("sscrt", &[]), msg).unwrap();
+        let expected = send_msg(
+            HumanAddr::from("forwarder b"),
+            Uint128(40),
+            Some(to_binary(&ReceiveMsg { distribution: None, split_id: None, hops: 4 }).unwrap()),
+            None,
+            BLOCK_SIZE,
+            String::from("sscrt hash"),
+            HumanAddr::from("sscrt"),
+        )
+        .unwrap();
+        assert_eq!(response.messages[1], expected);
+        // contract recipients without a msg are sent the hop count too
+        let expected = send_msg(
+            HumanAddr::from("pool"),
+            Uint128(10),
+            Some(to_binary(&ReceiveMsg { distribution: None, split_id: None, hops: 4 }).unwrap()),
+            None,
+            BLOCK_SIZE,
+            String::from("sscrt hash"),
+            HumanAddr::from("sscrt"),
+        )
+        .unwrap();
+        assert_eq!(response.messages[2], expected);
+
+        // funds that already passed through the max depth of forwarders are rejected
+        let msg = HandleMsg::Receive {
+            sender: HumanAddr::from("forwarder a"),
+            from: HumanAddr::from("forwarder a"),
+            amount: Uint128(100),
+            msg: Some(Binary::from(br#"{"hops":10}"#.to_vec())),
+        };
+        let result = handle(&mut deps, mock_env("sscrt", &[]), msg.clone());
+        match result {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!(msg, "Funds have been forwarded through too many contracts")
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        let depth = HandleMsg::SetMaxForwardDepth { max_forward_depth: 11 };
+        handle(&mut deps, mock_env("admin", &[]), depth).unwrap();
+        assert!(handle(&mut deps, mock_env("sscrt", &[]), msg).is_ok());
+        let depth = HandleMsg::SetMaxForwardDepth { max_forward_depth: 0 };
+        assert!(handle(&mut deps, mock_env("admin", &[]), depth).is_err());
+
+        // the contract can not pay itself
+        let dist_info = RoyaltyInfo {
+            decimal_places_in_rates: 0,
+            royalties: vec![Royalty {
+                recipient: HumanAddr::from("cosmos2contract"),
+                rate: 1,
+                code_hash: Some(String::from("own hash")),
+                msg: None,
+            }],
+            remainder_recipient: None,
+        };
+        let result = handle(&mut deps, mock_env("admin", &[]), HandleMsg::ChangeDistribution { dist_info });
+        match result {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!(msg, "The contract can not be a recipient of its own distribution")
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+}
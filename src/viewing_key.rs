@@ -0,0 +1,83 @@
+use std::fmt;
+
+use cosmwasm_std::{Binary, Env};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+pub const VIEWING_KEY_SIZE: usize = 32;
+pub const VIEWING_KEY_PREFIX: &str = "api_key_";
+
+/// a key used to authenticate queries
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct ViewingKey(pub String);
+
+impl ViewingKey {
+    /// Returns ViewingKey from hashing the contract's prng seed with the message's block and
+    /// sender information and the caller's entropy
+    ///
+    /// # Arguments
+    ///
+    /// * `env` - a reference to the Env of contract's environment
+    /// * `seed` - the contract's prng seed
+    /// * `entropy` - entropy supplied by the caller
+    pub fn new(env: &Env, seed: &[u8], entropy: &[u8]) -> Self {
+        // 16 here represents the lengths in bytes of the block height and time.
+        let entropy_len = seed.len() + 16 + env.message.sender.0.len() + entropy.len();
+        let mut rng_entropy = Vec::with_capacity(entropy_len);
+        rng_entropy.extend_from_slice(seed);
+        rng_entropy.extend_from_slice(&env.block.height.to_be_bytes());
+        rng_entropy.extend_from_slice(&env.block.time.to_be_bytes());
+        rng_entropy.extend_from_slice(env.message.sender.0.as_bytes());
+        rng_entropy.extend_from_slice(entropy);
+
+        let key = sha_256(&rng_entropy);
+
+        Self(VIEWING_KEY_PREFIX.to_string() + &Binary::from(&key[..]).to_base64())
+    }
+
+    /// Returns bool, true if this key hashes to the stored hash
+    ///
+    /// # Arguments
+    ///
+    /// * `hashed_pw` - the stored hash of the viewing key
+    pub fn check_viewing_key(&self, hashed_pw: &[u8]) -> bool {
+        let mine_hashed = self.to_hashed();
+
+        ct_slice_compare(&mine_hashed, hashed_pw)
+    }
+
+    /// Returns the hash of this key that is kept in storage
+    pub fn to_hashed(&self) -> [u8; VIEWING_KEY_SIZE] {
+        sha_256(self.0.as_bytes())
+    }
+}
+
+impl fmt::Display for ViewingKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Returns the sha256 hash of the data
+///
+/// # Arguments
+///
+/// * `data` - the data to hash
+pub fn sha_256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    let hash = hasher.finalize();
+
+    let mut result = [0u8; 32];
+    result.copy_from_slice(hash.as_slice());
+    result
+}
+
+/// Returns bool, true if the slices are equal, compared in constant time
+fn ct_slice_compare(s1: &[u8], s2: &[u8]) -> bool {
+    if s1.len() != s2.len() {
+        return false;
+    }
+    s1.iter().zip(s2.iter()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}